@@ -0,0 +1,78 @@
+//! Compares `Filter::matches` with `CompiledFilter::matches` on a
+//! subscription following many authors and tags.
+//!
+//! Run with `cargo bench --bench filter`
+
+use mosaic_core::*;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const ROUNDS: u32 = 2_000;
+
+fn main() {
+    let timestamp = Timestamp::from_unixtime(1_700_000_000, 0).unwrap();
+
+    // Records by a few authors, with a few kinds and tags
+    let keys: Vec<SecretKey> = (0..4).map(|_| SecretKey::generate()).collect();
+    let hashtags: Vec<OwnedTag> = (0..4)
+        .map(|i| OwnedTag::new_hashtag(&format!("tag{i}")).unwrap())
+        .collect();
+    let kinds = [
+        Kind::MICROBLOG_ROOT,
+        Kind::REPLY_COMMENT,
+        Kind::CHAT_MESSAGE,
+    ];
+    let records: Vec<OwnedRecord> = (0..48)
+        .map(|i| {
+            RecordBuilder::new(kinds[i % 3], keys[i % 4].clone())
+                .timestamp(timestamp + Duration::from_secs(i as u64))
+                .tag(&hashtags[i / 12])
+                .unwrap()
+                .sign()
+                .unwrap()
+        })
+        .collect();
+
+    let mut authors: Vec<PublicKey> = (0..60).map(|_| SecretKey::generate().public()).collect();
+    authors.push(keys[3].public());
+    let mut tags: Vec<OwnedTag> = (0..100)
+        .map(|i| OwnedTag::new_hashtag(&format!("other{i}")).unwrap())
+        .collect();
+    tags.push(hashtags[2].clone());
+    let filter = FilterBuilder::new()
+        .authors(authors)
+        .kinds([Kind::MICROBLOG_ROOT, Kind::REPLY_COMMENT, Kind::BLOG_POST])
+        .tags(&tags)
+        .since(timestamp)
+        .build()
+        .unwrap()
+        .remove(0);
+    let compiled = filter.compile().unwrap();
+
+    let start = Instant::now();
+    let mut parsed_count = 0;
+    for _ in 0..ROUNDS {
+        for record in &records {
+            if filter.matches(black_box(record)).unwrap() {
+                parsed_count += 1;
+            }
+        }
+    }
+    let parsed = start.elapsed();
+
+    let start = Instant::now();
+    let mut compiled_count = 0;
+    for _ in 0..ROUNDS {
+        for record in &records {
+            if compiled.matches(black_box(record)) {
+                compiled_count += 1;
+            }
+        }
+    }
+    let compiled = start.elapsed();
+
+    assert_eq!(parsed_count, compiled_count);
+    let n = ROUNDS * u32::try_from(records.len()).unwrap();
+    println!("Filter::matches         {:?}/record", parsed / n);
+    println!("CompiledFilter::matches {:?}/record", compiled / n);
+}
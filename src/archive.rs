@@ -0,0 +1,646 @@
+use crate::{Address, Error, Id, InnerError, OwnedRecord, PublicKey, Record};
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::ops::Range;
+
+// An archive (a `.mosaic` pack) is laid out as follows. All integers are
+// little-endian and every section starts on an 8-byte boundary.
+//
+//   Header (64 bytes):
+//     0..8    - Magic "MOSAICPK"
+//     8..12   - Version (1)
+//     12..16  - Reserved (zero)
+//     16..24  - Record count
+//     24..32  - Records section length (records start at 64)
+//     32..40  - Offset of the Id index
+//     40..48  - Offset of the Address index
+//     48..56  - Offset of the author index
+//     56..64  - Reserved (zero)
+//
+//   Records section: records concatenated, each in the `Record` layout
+//
+//   Id index: (Id[48], record offset u64) entries sorted by Id
+//   Address index: (Address[48], record offset u64) entries sorted by
+//     Address and then by Id
+//   Author index: (PublicKey[32], record offset u64) entries sorted by
+//     author and then by Id
+//
+// Record offsets are from the start of the archive.
+
+const MAGIC: [u8; 8] = *b"MOSAICPK";
+const VERSION: u32 = 1;
+const HEADER_LEN: usize = 64;
+
+const ID_KEY_LEN: usize = 48;
+const ADDRESS_KEY_LEN: usize = 48;
+const AUTHOR_KEY_LEN: usize = 32;
+
+/// An indexed archive of `Record`s, viewed zero-copy over its bytes
+///
+/// Records can be looked up by `Id`, by `Address` and by author without
+/// scanning the archive. With the `mmap` feature, a `MappedArchive` reads an
+/// archive file through a memory map.
+///
+/// Archives are created with an `ArchiveWriter`.
+#[derive(Debug, Clone, Copy)]
+pub struct Archive<'a> {
+    bytes: &'a [u8],
+    record_count: usize,
+    records_end: usize,
+    id_index: Index<'a>,
+    address_index: Index<'a>,
+    author_index: Index<'a>,
+    verified: bool,
+}
+
+impl<'a> Archive<'a> {
+    /// Interpret a sequence of bytes as an `Archive`. Checks validity,
+    /// including verifying every record.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the header or any index is invalid, if any index
+    /// disagrees with the records, or if any record fails verification.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Archive<'a>, Error> {
+        let mut archive = Self::parse(bytes)?;
+        archive.verify()?;
+        archive.verified = true;
+        Ok(archive)
+    }
+
+    /// Interpret a sequence of bytes as an `Archive`. Checks validity of the
+    /// header only. Records are verified as they are looked up.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the header is invalid or the sections it describes
+    /// don't fit in the `bytes`.
+    ///
+    /// # Safety
+    ///
+    /// Bytes must be a valid `Archive`, otherwise undefined results can occur
+    /// including panics
+    pub unsafe fn from_bytes_unchecked(bytes: &'a [u8]) -> Result<Archive<'a>, Error> {
+        Self::parse(bytes)
+    }
+
+    // Check the header and that the sections it describes fit in the bytes.
+    // Until `verified` is set, records are verified as they are looked up.
+    fn parse(bytes: &'a [u8]) -> Result<Archive<'a>, Error> {
+        if bytes.len() < HEADER_LEN {
+            return Err(InnerError::EndOfInput.into());
+        }
+        if bytes[0..8] != MAGIC || read_u32(bytes, 8) != VERSION || read_u32(bytes, 12) != 0 {
+            return Err(InnerError::InvalidArchive.into());
+        }
+
+        let record_count = usize::try_from(read_u64(bytes, 16))?;
+        let records_len = usize::try_from(read_u64(bytes, 24))?;
+        let records_end = HEADER_LEN
+            .checked_add(records_len)
+            .ok_or(InnerError::InvalidArchive.into_err())?;
+        if records_end > bytes.len() {
+            return Err(InnerError::EndOfInput.into());
+        }
+
+        let index = |at: usize, key_len: usize| -> Result<Index<'a>, Error> {
+            let start = usize::try_from(read_u64(bytes, at))?;
+            let len = record_count
+                .checked_mul(key_len + 8)
+                .ok_or(InnerError::InvalidArchive.into_err())?;
+            let end = start
+                .checked_add(len)
+                .ok_or(InnerError::InvalidArchive.into_err())?;
+            if start < records_end || start % 8 != 0 {
+                return Err(InnerError::InvalidArchive.into());
+            }
+            if end > bytes.len() {
+                return Err(InnerError::EndOfInput.into());
+            }
+            Ok(Index {
+                bytes: &bytes[start..end],
+                key_len,
+            })
+        };
+
+        Ok(Archive {
+            bytes,
+            record_count,
+            records_end,
+            id_index: index(32, ID_KEY_LEN)?,
+            address_index: index(40, ADDRESS_KEY_LEN)?,
+            author_index: index(48, AUTHOR_KEY_LEN)?,
+            verified: false,
+        })
+    }
+
+    /// Verify that the records are all valid and that the indexes agree with
+    /// them. `from_bytes()` calls this.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if any record fails verification, or if any index
+    /// disagrees with the records.
+    pub fn verify(&self) -> Result<(), Error> {
+        // Walk the records
+        let mut records: Vec<&Record> = Vec::with_capacity(self.record_count);
+        let mut offsets: HashMap<usize, usize> = HashMap::with_capacity(self.record_count);
+        let mut offset = HEADER_LEN;
+        while offset < self.records_end {
+            let record =
+                unsafe { Record::from_bytes_unchecked(&self.bytes[offset..self.records_end])? };
+            let _ = offsets.insert(offset, records.len());
+            records.push(record);
+            offset += record.as_bytes().len();
+        }
+        if records.len() != self.record_count {
+            return Err(InnerError::InvalidArchive.into());
+        }
+
+        if let Err(mut failures) = Record::verify_batch(&records) {
+            return Err(failures.swap_remove(0).1);
+        }
+
+        // Check every index refers to every record exactly once, under the
+        // right key and in order
+        let check = |index: &Index<'a>,
+                     key: &dyn Fn(&Record) -> Vec<u8>,
+                     unique: bool|
+         -> Result<(), Error> {
+            let mut seen = vec![false; records.len()];
+            let mut previous: Option<(&[u8], Id)> = None;
+            for i in 0..index.len() {
+                let record_index = *offsets
+                    .get(&index.offset(i))
+                    .ok_or(InnerError::InvalidArchive.into_err())?;
+                let record = records[record_index];
+                if seen[record_index] || key(record) != index.key(i) {
+                    return Err(InnerError::InvalidArchive.into());
+                }
+                seen[record_index] = true;
+
+                let sort_key = (index.key(i), record.id());
+                if let Some(previous) = &previous {
+                    if *previous > sort_key || (unique && previous.0 == sort_key.0) {
+                        return Err(InnerError::InvalidArchive.into());
+                    }
+                }
+                previous = Some(sort_key);
+            }
+            Ok(())
+        };
+        check(&self.id_index, &|r| r.id().as_bytes().to_vec(), true)?;
+        check(
+            &self.address_index,
+            &|r| r.address().as_bytes().to_vec(),
+            false,
+        )?;
+        check(
+            &self.author_index,
+            &|r| r.author_public_key().as_bytes().to_vec(),
+            false,
+        )?;
+
+        Ok(())
+    }
+
+    /// As bytes
+    #[must_use]
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// The number of records in the archive
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.record_count
+    }
+
+    /// Whether the archive has no records
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.record_count == 0
+    }
+
+    /// Iterate over all of the records, in the order they were written
+    ///
+    /// If the archive was not verified, records that fail verification are
+    /// skipped.
+    #[must_use]
+    pub fn records(&self) -> ArchiveIter<'a> {
+        ArchiveIter {
+            bytes: &self.bytes[..self.records_end],
+            offset: HEADER_LEN,
+            verified: self.verified,
+        }
+    }
+
+    /// Get the record with the given `Id`
+    #[must_use]
+    pub fn get_by_id(&self, id: &Id) -> Option<&'a Record> {
+        let range = self.id_index.range(id.as_bytes());
+        if range.is_empty() {
+            None
+        } else {
+            self.record_at(self.id_index.offset(range.start))
+                .filter(|r| r.id() == *id)
+        }
+    }
+
+    /// Get the latest record with the given `Address`
+    #[must_use]
+    pub fn get_by_address(&self, address: &Address) -> Option<&'a Record> {
+        let range = self.address_index.range(address.as_bytes());
+        if range.is_empty() {
+            None
+        } else {
+            self.record_at(self.address_index.offset(range.end - 1))
+                .filter(|r| r.address() == *address)
+        }
+    }
+
+    /// Iterate over every record with the given `Address`, oldest first
+    pub fn records_by_address(&self, address: &Address) -> impl Iterator<Item = &'a Record> {
+        let archive = *self;
+        let address = *address;
+        self.address_index
+            .range(address.as_bytes())
+            .filter_map(move |i| archive.record_at(archive.address_index.offset(i)))
+            .filter(move |r| r.address() == address)
+    }
+
+    /// Iterate over every record by the given author, oldest first
+    pub fn records_by_author(&self, author: &PublicKey) -> impl Iterator<Item = &'a Record> {
+        let archive = *self;
+        let author = *author;
+        self.author_index
+            .range(author.as_bytes())
+            .filter_map(move |i| archive.record_at(archive.author_index.offset(i)))
+            .filter(move |r| r.author_public_key() == author)
+    }
+
+    // The record at the offset, if there is one and it is valid
+    fn record_at(&self, offset: usize) -> Option<&'a Record> {
+        if offset < HEADER_LEN || offset >= self.records_end {
+            return None;
+        }
+        read_record(&self.bytes[offset..self.records_end], self.verified)
+    }
+}
+
+/// An iterator over the `Record`s of an `Archive`
+#[derive(Debug)]
+pub struct ArchiveIter<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    verified: bool,
+}
+
+impl<'a> Iterator for ArchiveIter<'a> {
+    type Item = &'a Record;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.offset < self.bytes.len() {
+            let record = unsafe { Record::from_bytes_unchecked(&self.bytes[self.offset..]).ok()? };
+            self.offset += record.as_bytes().len();
+            if self.verified || record.verify().is_ok() {
+                return Some(record);
+            }
+        }
+        None
+    }
+}
+
+// Read the record at the start of the bytes, verifying it unless the whole
+// archive has been verified
+fn read_record(bytes: &[u8], verified: bool) -> Option<&Record> {
+    if verified {
+        unsafe { Record::from_bytes_unchecked(bytes).ok() }
+    } else {
+        Record::from_bytes(bytes).ok()
+    }
+}
+
+// A sorted index section: fixed length entries of a key then a u64 offset
+#[derive(Debug, Clone, Copy)]
+struct Index<'a> {
+    bytes: &'a [u8],
+    key_len: usize,
+}
+
+impl Index<'_> {
+    fn len(&self) -> usize {
+        self.bytes.len() / (self.key_len + 8)
+    }
+
+    fn key(&self, i: usize) -> &[u8] {
+        let start = i * (self.key_len + 8);
+        &self.bytes[start..start + self.key_len]
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn offset(&self, i: usize) -> usize {
+        read_u64(self.bytes, i * (self.key_len + 8) + self.key_len) as usize
+    }
+
+    // The range of entries with the given key
+    fn range(&self, key: &[u8]) -> Range<usize> {
+        let start = self.partition_point(|k| k < key);
+        let end = self.partition_point(|k| k <= key);
+        start..end
+    }
+
+    fn partition_point(&self, pred: impl Fn(&[u8]) -> bool) -> usize {
+        let (mut low, mut high) = (0, self.len());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if pred(self.key(mid)) {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        low
+    }
+}
+
+/// Assembles an `Archive`
+#[derive(Debug, Default)]
+pub struct ArchiveWriter {
+    records: Vec<OwnedRecord>,
+    ids: HashSet<Id>,
+}
+
+impl ArchiveWriter {
+    /// Create a new, empty `ArchiveWriter`
+    #[must_use]
+    pub fn new() -> ArchiveWriter {
+        ArchiveWriter::default()
+    }
+
+    /// Add a `Record`. Returns false (and does nothing) if a record with the
+    /// same `Id` was already added.
+    pub fn add_record(&mut self, record: &Record) -> bool {
+        if !self.ids.insert(record.id()) {
+            return false;
+        }
+        self.records.push(record.to_owned());
+        true
+    }
+
+    /// The number of records added
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Whether no records have been added
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Assemble the archive bytes
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut offsets: Vec<usize> = Vec::with_capacity(self.records.len());
+        let mut records_len = 0;
+        for record in &self.records {
+            offsets.push(HEADER_LEN + records_len);
+            records_len += record.as_bytes().len();
+        }
+
+        let index = |key: &dyn Fn(&Record) -> Vec<u8>| -> Vec<u8> {
+            let mut entries: Vec<(Vec<u8>, Id, usize)> = self
+                .records
+                .iter()
+                .zip(offsets.iter())
+                .map(|(r, o)| (key(r), r.id(), *o))
+                .collect();
+            entries.sort();
+            let mut bytes = Vec::new();
+            for (key, _, offset) in entries {
+                bytes.extend_from_slice(&key);
+                bytes.extend_from_slice((offset as u64).to_le_bytes().as_slice());
+            }
+            bytes
+        };
+        let id_index = index(&|r| r.id().as_bytes().to_vec());
+        let address_index = index(&|r| r.address().as_bytes().to_vec());
+        let author_index = index(&|r| r.author_public_key().as_bytes().to_vec());
+
+        let id_index_offset = HEADER_LEN + records_len;
+        let address_index_offset = id_index_offset + id_index.len();
+        let author_index_offset = address_index_offset + address_index.len();
+
+        let mut bytes = Vec::with_capacity(author_index_offset + author_index.len());
+        bytes.extend_from_slice(&MAGIC);
+        bytes.extend_from_slice(VERSION.to_le_bytes().as_slice());
+        bytes.extend_from_slice(&[0; 4]);
+        for n in [
+            self.records.len(),
+            records_len,
+            id_index_offset,
+            address_index_offset,
+            author_index_offset,
+            0,
+        ] {
+            bytes.extend_from_slice((n as u64).to_le_bytes().as_slice());
+        }
+        for record in &self.records {
+            bytes.extend_from_slice(record.as_bytes());
+        }
+        bytes.extend_from_slice(&id_index);
+        bytes.extend_from_slice(&address_index);
+        bytes.extend_from_slice(&author_index);
+        bytes
+    }
+
+    /// Write the archive
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` on I/O errors
+    pub fn write_to<W: Write>(&self, mut writer: W) -> Result<(), Error> {
+        writer.write_all(&self.to_bytes())?;
+        Ok(())
+    }
+}
+
+/// An `Archive` file read through a memory map
+#[cfg(feature = "mmap")]
+#[derive(Debug)]
+pub struct MappedArchive {
+    mmap: memmap2::Mmap,
+}
+
+#[cfg(feature = "mmap")]
+impl MappedArchive {
+    /// Open and map an archive file.
+    ///
+    /// Only the header is checked, so that opening a large archive does not
+    /// read all of it. Each record is verified as it is looked up, and
+    /// `verify()` checks the whole archive.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated, by this or any other
+    /// process, while the `MappedArchive` exists. Doing so is undefined
+    /// behavior.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the file cannot be opened or mapped, or if its
+    /// header is invalid.
+    pub unsafe fn open<P: AsRef<std::path::Path>>(path: P) -> Result<MappedArchive, Error> {
+        let file = std::fs::File::open(path)?;
+        let mmap = unsafe { memmap2::Mmap::map(&file)? };
+        let _ = Archive::parse(&mmap)?;
+        Ok(MappedArchive { mmap })
+    }
+
+    /// Verify every record and index of the archive
+    ///
+    /// # Errors
+    ///
+    /// See `Archive::verify()`
+    pub fn verify(&self) -> Result<(), Error> {
+        self.archive().verify()
+    }
+
+    /// View the `Archive`
+    ///
+    /// Records are verified as they are looked up.
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn archive(&self) -> Archive<'_> {
+        // The header was checked when opened
+        Archive::parse(&self.mmap).unwrap()
+    }
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+}
+
+fn read_u64(bytes: &[u8], at: usize) -> u64 {
+    u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn test_archive() {
+        let alice = SecretKey::generate();
+        let bob = SecretKey::generate();
+        let timestamp = Timestamp::from_unixtime(1_700_000_000, 0).unwrap();
+
+        let mut records: Vec<OwnedRecord> = Vec::new();
+        for (i, key) in [&alice, &bob, &alice, &bob, &alice].into_iter().enumerate() {
+            records.push(
+                RecordBuilder::new(Kind::MICROBLOG_ROOT, key.clone())
+                    .payload(format!("post {i}").as_bytes())
+                    .unwrap()
+                    .timestamp(timestamp + std::time::Duration::from_secs(i as u64))
+                    .sign()
+                    .unwrap(),
+            );
+        }
+        // Two versions of alice's profile share an address
+        for i in 0..2 {
+            records.push(
+                RecordBuilder::new(Kind::PROFILE, alice.clone())
+                    .deterministic_address(b"profile")
+                    .payload(format!("version {i}").as_bytes())
+                    .unwrap()
+                    .timestamp(timestamp + std::time::Duration::from_secs(10 + i))
+                    .sign()
+                    .unwrap(),
+            );
+        }
+
+        let mut writer = ArchiveWriter::new();
+        for record in &records {
+            assert!(writer.add_record(record));
+        }
+        assert!(!writer.add_record(&records[0]));
+        let bytes = writer.to_bytes();
+
+        let archive = Archive::from_bytes(&bytes).unwrap();
+        assert_eq!(archive.len(), 7);
+        assert_eq!(archive.records().count(), 7);
+        for record in &records {
+            assert_eq!(archive.get_by_id(&record.id()), Some(&**record));
+        }
+        assert_eq!(archive.records_by_author(&alice.public()).count(), 5);
+        assert_eq!(archive.records_by_author(&bob.public()).count(), 2);
+
+        let address = records[5].address();
+        assert_eq!(archive.records_by_address(&address).count(), 2);
+        assert_eq!(archive.get_by_address(&address), Some(&*records[6]));
+        assert_eq!(
+            archive.get_by_address(&records[1].address()),
+            Some(&*records[1])
+        );
+
+        let missing = RecordBuilder::new(Kind::EXAMPLE, bob.clone())
+            .timestamp(timestamp)
+            .sign()
+            .unwrap();
+        assert!(archive.get_by_id(&missing.id()).is_none());
+        assert!(archive.get_by_address(&missing.address()).is_none());
+
+        // Corrupted records are detected
+        let mut corrupted = bytes.clone();
+        corrupted[64 + 200] ^= 1;
+        assert!(Archive::from_bytes(&corrupted).is_err());
+
+        // As are corrupted indexes
+        let mut corrupted = bytes.clone();
+        let last = corrupted.len() - 1;
+        corrupted[last] ^= 1;
+        assert!(Archive::from_bytes(&corrupted).is_err());
+
+        // An empty archive is fine
+        let empty = ArchiveWriter::new().to_bytes();
+        assert!(Archive::from_bytes(&empty).unwrap().is_empty());
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_mapped_archive() {
+        let record = RecordBuilder::new(Kind::EXAMPLE, SecretKey::generate())
+            .timestamp(Timestamp::from_unixtime(1_700_000_000, 0).unwrap())
+            .sign()
+            .unwrap();
+        let mut writer = ArchiveWriter::new();
+        assert!(writer.add_record(&record));
+
+        let path = std::env::temp_dir().join(format!("{}.mosaic", record.id().as_printable()));
+        writer
+            .write_to(std::fs::File::create(&path).unwrap())
+            .unwrap();
+
+        let mapped = unsafe { MappedArchive::open(&path).unwrap() };
+        mapped.verify().unwrap();
+        assert_eq!(mapped.archive().get_by_id(&record.id()), Some(&*record));
+        drop(mapped);
+
+        // A corrupted record is only found when it is looked up or verified
+        let mut bytes = writer.to_bytes();
+        bytes[64 + 200] ^= 1;
+        std::fs::write(&path, &bytes).unwrap();
+        let mapped = unsafe { MappedArchive::open(&path).unwrap() };
+        assert!(mapped.archive().get_by_id(&record.id()).is_none());
+        assert_eq!(mapped.archive().records().count(), 0);
+        assert!(mapped.verify().is_err());
+        drop(mapped);
+
+        std::fs::write(&path, &bytes[..32]).unwrap();
+        assert!(unsafe { MappedArchive::open(&path) }.is_err());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
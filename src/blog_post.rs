@@ -0,0 +1,261 @@
+use crate::microblog::{builder, content};
+use crate::{
+    Address, Error, InnerError, Kind, OwnedRecord, OwnedTag, OwnedTagSet, PublicKey, Record,
+    RecordBuilder, Signer, Tag, TagType, Timestamp,
+};
+
+/// The cover image of a `BlogPost`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CoverImage {
+    /// An image on the web
+    Url(String),
+
+    /// A blob, by its hash
+    BlobHash([u8; 32]),
+}
+
+/// A long-form article with its metadata (a `BLOG_POST` record)
+///
+/// The payload is the Markdown body. The title is in a `SUBJECT` tag, and
+/// the slug, summary, cover image and publication time are in `SLUG`,
+/// `SUMMARY`, `COVER_IMAGE_URL` or `COVER_IMAGE_BLOB`, and `PUBLISHED_AT`
+/// tags.
+///
+/// The record's address is determined by the author and the slug, so every
+/// edit of a post shares its address, and the versions are kept as a
+/// history.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BlogPost {
+    /// Title
+    pub title: String,
+
+    /// A short summary, for listings
+    pub summary: Option<String>,
+
+    /// Cover image
+    pub cover_image: Option<CoverImage>,
+
+    /// When the post was first published (the record's timestamp is when
+    /// this version was written)
+    pub published_at: Option<Timestamp>,
+
+    /// The post, in Markdown
+    pub body: String,
+
+    /// Short name of the post, unique among the author's posts (e.g. for URLs)
+    pub slug: String,
+}
+
+impl BlogPost {
+    /// Create a new blog post
+    #[must_use]
+    pub fn new(slug: &str, title: &str, body: &str) -> BlogPost {
+        BlogPost {
+            title: title.to_owned(),
+            summary: None,
+            cover_image: None,
+            published_at: None,
+            body: body.to_owned(),
+            slug: slug.to_owned(),
+        }
+    }
+
+    /// The tags holding the metadata of the post
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the slug, title, summary or cover image URL is too
+    /// long for a tag.
+    pub fn tags(&self) -> Result<Vec<OwnedTag>, Error> {
+        let mut tags = vec![
+            OwnedTag::new_slug(&self.slug)?,
+            OwnedTag::new_subject(&self.title)?,
+        ];
+        if let Some(summary) = &self.summary {
+            tags.push(OwnedTag::new_summary(summary)?);
+        }
+        match &self.cover_image {
+            Some(CoverImage::Url(url)) => tags.push(OwnedTag::new_cover_image_url(url)?),
+            Some(CoverImage::BlobHash(hash)) => tags.push(OwnedTag::new_cover_image_blob(hash)),
+            None => {}
+        }
+        if let Some(published_at) = self.published_at {
+            tags.push(OwnedTag::new_published_at(published_at));
+        }
+        Ok(tags)
+    }
+
+    /// A `RecordBuilder` for a record of this post, at the address for its
+    /// slug, to be signed by the author's `Signer` (such as their
+    /// `SecretKey`). Set the timestamp or flags on it before signing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the post is too large to fit in a record.
+    pub fn record_builder<S: Signer + 'static>(
+        &self,
+        author: PublicKey,
+        signer: S,
+    ) -> Result<RecordBuilder, Error> {
+        Ok(builder(
+            Kind::BLOG_POST,
+            author,
+            signer,
+            &self.tags()?,
+            &OwnedTagSet::new(),
+            &self.body,
+        )?
+        .deterministic_address(self.slug.as_bytes()))
+    }
+
+    /// Create a new `OwnedRecord` of this post, at the address for its slug,
+    /// signed by the author's `Signer` (such as their `SecretKey`)
+    ///
+    /// With the `zstd` feature the payload is compressed if that saves space.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the post is too large to fit in a record, or if
+    /// signing fails.
+    pub fn as_record<S: Signer + 'static>(
+        &self,
+        author: PublicKey,
+        signer: S,
+    ) -> Result<OwnedRecord, Error> {
+        self.record_builder(author, signer)?.sign()
+    }
+
+    /// Extract a `BlogPost` from a `BLOG_POST` `Record`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Record is the wrong kind, or doesn't validate,
+    /// or its content is not UTF-8, or it has no `SLUG` or `SUBJECT` tag, or
+    /// its metadata tags are invalid, or its address is not the one for its
+    /// slug.
+    pub fn from_record(record: &Record) -> Result<BlogPost, Error> {
+        let body = content(record, Kind::BLOG_POST)?;
+
+        let tag_set = record.tag_set();
+        let tag = |ty: TagType| tag_set.first_of_type(ty);
+        let required = |ty: TagType| tag(ty).ok_or::<Error>(InnerError::MissingTag(ty).into());
+
+        let slug = required(TagType::SLUG)?.get_slug()?.unwrap_or_default();
+        let address = Address::new_deterministic(
+            record.author_public_key(),
+            Kind::BLOG_POST,
+            slug.as_bytes(),
+        );
+        if record.address() != address {
+            return Err(InnerError::AddressMismatch.into());
+        }
+
+        let cover_image = match (
+            tag(TagType::COVER_IMAGE_URL),
+            tag(TagType::COVER_IMAGE_BLOB),
+        ) {
+            (Some(url), _) => url
+                .get_cover_image_url()?
+                .map(|u| CoverImage::Url(u.to_owned())),
+            (None, Some(hash)) => hash.get_cover_image_blob()?.map(CoverImage::BlobHash),
+            (None, None) => None,
+        };
+
+        Ok(BlogPost {
+            title: required(TagType::SUBJECT)?
+                .get_subject()?
+                .unwrap_or_default()
+                .to_owned(),
+            summary: tag(TagType::SUMMARY)
+                .map(Tag::get_summary)
+                .transpose()?
+                .flatten()
+                .map(str::to_owned),
+            cover_image,
+            published_at: tag(TagType::PUBLISHED_AT)
+                .map(Tag::get_published_at)
+                .transpose()?
+                .flatten(),
+            body,
+            slug: slug.to_owned(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn test_blog_post() {
+        let secret_key = SecretKey::generate();
+        let timestamp = Timestamp::from_unixtime(1_700_000_000, 0).unwrap();
+
+        let mut post = BlogPost::new("hello", "Hello", "# Hello\n\nWorld");
+        post.summary = Some("A greeting".to_owned());
+        post.cover_image = Some(CoverImage::BlobHash([7; 32]));
+        post.published_at = Some(timestamp);
+
+        let record = post
+            .record_builder(secret_key.public(), secret_key.clone())
+            .unwrap()
+            .timestamp(timestamp)
+            .sign()
+            .unwrap();
+        assert_eq!(BlogPost::from_record(&record).unwrap(), post);
+        assert_eq!(
+            record.address(),
+            Address::new_deterministic(secret_key.public(), Kind::BLOG_POST, b"hello")
+        );
+        assert!(record.to_string().contains("# Hello\n\nWorld"));
+        #[cfg(feature = "json")]
+        assert_eq!(&OwnedRecord::from_json(&record.as_json()).unwrap(), &record);
+
+        // An edit is a new version at the same address
+        post.body.push('!');
+        post.cover_image = Some(CoverImage::Url("https://example.com/a.png".to_owned()));
+        let edit = post
+            .record_builder(secret_key.public(), secret_key.clone())
+            .unwrap()
+            .timestamp(timestamp + std::time::Duration::from_mins(1))
+            .sign()
+            .unwrap();
+        assert_eq!(edit.address(), record.address());
+        assert_ne!(edit.id(), record.id());
+        assert_eq!(BlogPost::from_record(&edit).unwrap(), post);
+
+        // The address must be the one for the slug
+        let moved = RecordBuilder::new(Kind::BLOG_POST, secret_key.clone())
+            .deterministic_address(b"elsewhere")
+            .tag(&OwnedTag::new_slug("hello").unwrap())
+            .unwrap()
+            .tag(&OwnedTag::new_subject("Hello").unwrap())
+            .unwrap()
+            .timestamp(timestamp)
+            .sign()
+            .unwrap();
+        let e = BlogPost::from_record(&moved).unwrap_err();
+        assert!(matches!(e.inner, InnerError::AddressMismatch));
+
+        // The slug and title are required
+        let untitled = RecordBuilder::new(Kind::BLOG_POST, secret_key.clone())
+            .deterministic_address(b"hello")
+            .tag(&OwnedTag::new_slug("hello").unwrap())
+            .unwrap()
+            .timestamp(timestamp)
+            .sign()
+            .unwrap();
+        let e = BlogPost::from_record(&untitled).unwrap_err();
+        assert!(matches!(e.inner, InnerError::MissingTag(TagType::SUBJECT)));
+
+        // Kinds are checked
+        let note = MicroblogRoot::new("Hi")
+            .record_builder(secret_key.public(), secret_key)
+            .unwrap()
+            .timestamp(timestamp)
+            .sign()
+            .unwrap();
+        let e = BlogPost::from_record(&note).unwrap_err();
+        assert!(matches!(e.inner, InnerError::WrongKind));
+    }
+}
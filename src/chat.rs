@@ -0,0 +1,138 @@
+use crate::microblog::{builder, content, threading_tags};
+use crate::{
+    Error, InnerError, Kind, OwnedRecord, OwnedTag, OwnedTagSet, PublicKey, Record, RecordBuilder,
+    Reference, Signer, TagType,
+};
+
+/// A message in a chat channel (a `CHAT_MESSAGE` record)
+///
+/// The channel is the record that defines it, referred to by a `ROOT` tag.
+/// A message answering an earlier one also has a `REPLY` tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatMessage {
+    /// The channel, and its kind
+    pub channel: (Reference, Kind),
+
+    /// The message this answers, and its kind
+    pub reply_to: Option<(Reference, Kind)>,
+
+    /// The text of the message
+    pub content: String,
+
+    /// Any other tags, such as mentions
+    pub tags: OwnedTagSet,
+}
+
+impl ChatMessage {
+    /// Create a new message in the channel
+    #[must_use]
+    pub fn new(channel: Reference, channel_kind: Kind, content: &str) -> ChatMessage {
+        ChatMessage {
+            channel: (channel, channel_kind),
+            reply_to: None,
+            content: content.to_owned(),
+            tags: OwnedTagSet::new(),
+        }
+    }
+
+    /// A `RecordBuilder` for a record of this message, to be signed by the
+    /// author's `Signer` (such as their `SecretKey`, or a subkey's). Set the
+    /// timestamp or flags on it before signing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the message is too large to fit in a record.
+    pub fn record_builder<S: Signer + 'static>(
+        &self,
+        author: PublicKey,
+        signer: S,
+    ) -> Result<RecordBuilder, Error> {
+        let mut threading = vec![OwnedTag::new_root(&self.channel.0, self.channel.1)];
+        if let Some((reference, kind)) = &self.reply_to {
+            threading.push(OwnedTag::new_reply(reference, *kind));
+        }
+        builder(
+            Kind::CHAT_MESSAGE,
+            author,
+            signer,
+            &threading,
+            &self.tags,
+            &self.content,
+        )
+    }
+
+    /// Create a new `OwnedRecord` of this message, signed by the author's
+    /// `Signer` (such as their `SecretKey`, or a subkey's)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the message is too large to fit in a record, or if
+    /// signing fails.
+    pub fn as_record<S: Signer + 'static>(
+        &self,
+        author: PublicKey,
+        signer: S,
+    ) -> Result<OwnedRecord, Error> {
+        self.record_builder(author, signer)?.sign()
+    }
+
+    /// Extract a `ChatMessage` from a `CHAT_MESSAGE` `Record`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Record is the wrong kind, or doesn't validate,
+    /// or its content is not UTF-8, or it lacks a `ROOT` tag for the channel.
+    pub fn from_record(record: &Record) -> Result<ChatMessage, Error> {
+        let content = content(record, Kind::CHAT_MESSAGE)?;
+        let (reply_to, channel, tags) = threading_tags(record);
+        Ok(ChatMessage {
+            channel: channel.ok_or(InnerError::MissingTag(TagType::ROOT).into_err())?,
+            reply_to,
+            content,
+            tags,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn test_chat_message() {
+        let secret_key = SecretKey::generate();
+        let timestamp = Timestamp::from_unixtime(1_700_000_000, 0).unwrap();
+        let channel = Address::new_random(secret_key.public(), Kind::EXAMPLE).to_reference();
+
+        let message = ChatMessage::new(channel, Kind::EXAMPLE, "Anyone here?");
+        let record = message
+            .record_builder(secret_key.public(), secret_key.clone())
+            .unwrap()
+            .timestamp(timestamp)
+            .sign()
+            .unwrap();
+        assert_eq!(ChatMessage::from_record(&record).unwrap(), message);
+
+        let mut answer = ChatMessage::new(channel, Kind::EXAMPLE, "Yes");
+        answer.reply_to = Some((record.id().to_reference(), Kind::CHAT_MESSAGE));
+        answer
+            .tags
+            .add_tag(&OwnedTag::new_notify_public_key(&secret_key.public()));
+        let record = answer
+            .record_builder(secret_key.public(), secret_key.clone())
+            .unwrap()
+            .timestamp(timestamp)
+            .sign()
+            .unwrap();
+        assert_eq!(ChatMessage::from_record(&record).unwrap(), answer);
+
+        let post = MicroblogRoot::new("Not chat")
+            .record_builder(secret_key.public(), secret_key)
+            .unwrap()
+            .timestamp(timestamp)
+            .sign()
+            .unwrap();
+        let e = ChatMessage::from_record(&post).unwrap_err();
+        assert!(matches!(e.inner, InnerError::WrongKind));
+    }
+}
@@ -0,0 +1,477 @@
+use crate::{
+    Error, InnerError, Kind, OwnedTag, OwnedTagSet, ParsedTag, PublicKey, Reference, TagSet,
+};
+
+/// A span of rich text content
+///
+/// Content segment tags mark a segment to be inserted at a byte offset into
+/// the (printable) payload. The text between them is `Text`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContentSpan<'a> {
+    /// Plain text from the payload
+    Text(&'a str),
+
+    /// A mention of a user
+    UserMention(PublicKey),
+
+    /// A mention of a server
+    ServerMention(PublicKey),
+
+    /// A quote of another record, of the given `Kind`
+    Quote(Reference, Kind),
+
+    /// A link to a URL
+    Link(&'a str),
+
+    /// An image at a URL
+    Image(&'a str),
+
+    /// A video at a URL
+    Video(&'a str),
+}
+
+/// Printable content split into `ContentSpan`s according to its content
+/// segment tags, ready to be displayed
+///
+/// ```
+/// # use mosaic_core::*;
+/// let alice = SecretKey::generate().public();
+/// let mut tag_set = OwnedTagSet::new();
+/// tag_set.add_tag(&OwnedTag::new_content_segment_user_mention(&alice, 6));
+///
+/// let rich_text = RichText::new("Hello , welcome!", &tag_set).unwrap();
+/// assert_eq!(
+///     rich_text.spans(),
+///     &[
+///         ContentSpan::Text("Hello "),
+///         ContentSpan::UserMention(alice),
+///         ContentSpan::Text(", welcome!"),
+///     ]
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RichText<'a> {
+    spans: Vec<ContentSpan<'a>>,
+}
+
+impl<'a> RichText<'a> {
+    /// Split the `content` into spans at the offsets of the content segment
+    /// tags in the `tag_set`. Other tags are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if a content segment tag is invalid, if an offset is
+    /// beyond the end of the content or not on a UTF-8 character boundary, or
+    /// if two segments overlap.
+    pub fn new(content: &'a str, tag_set: &'a TagSet) -> Result<RichText<'a>, Error> {
+        let mut segments: Vec<(u32, ContentSpan<'a>)> = Vec::new();
+        for tag in tag_set {
+            if !tag.get_type().is_content_segment() {
+                continue;
+            }
+            segments.push(match tag.parse()? {
+                ParsedTag::UserMention { public_key, offset } => {
+                    (offset, ContentSpan::UserMention(public_key))
+                }
+                ParsedTag::ServerMention { public_key, offset } => {
+                    (offset, ContentSpan::ServerMention(public_key))
+                }
+                ParsedTag::Quote {
+                    reference,
+                    kind,
+                    offset,
+                } => (offset, ContentSpan::Quote(reference, kind)),
+                ParsedTag::Url { url, offset } => (offset, ContentSpan::Link(url)),
+                ParsedTag::Image { url, offset } => (offset, ContentSpan::Image(url)),
+                ParsedTag::Video { url, offset } => (offset, ContentSpan::Video(url)),
+                _ => continue,
+            });
+        }
+        segments.sort_by_key(|(offset, _)| *offset);
+
+        let mut spans: Vec<ContentSpan<'a>> = Vec::with_capacity(segments.len() * 2 + 1);
+        let mut position: usize = 0;
+        let mut last_offset: Option<u32> = None;
+        for (offset, span) in segments {
+            if last_offset == Some(offset) {
+                return Err(InnerError::ContentSegmentsOverlap(offset).into());
+            }
+            last_offset = Some(offset);
+
+            let at = usize::try_from(offset)?;
+            if at > content.len() {
+                return Err(InnerError::ContentSegmentOutOfRange(offset).into());
+            }
+            let Some(text) = content.get(position..at) else {
+                return Err(InnerError::ContentSegmentNotOnCharBoundary(offset).into());
+            };
+            if !text.is_empty() {
+                spans.push(ContentSpan::Text(text));
+            }
+            spans.push(span);
+            position = at;
+        }
+        if let Some(text) = content.get(position..) {
+            if !text.is_empty() {
+                spans.push(ContentSpan::Text(text));
+            }
+        }
+
+        Ok(RichText { spans })
+    }
+
+    /// The spans, in order
+    #[must_use]
+    pub fn spans(&self) -> &[ContentSpan<'a>] {
+        &self.spans
+    }
+
+    /// Take the spans
+    #[must_use]
+    pub fn into_spans(self) -> Vec<ContentSpan<'a>> {
+        self.spans
+    }
+}
+
+impl<'a> IntoIterator for RichText<'a> {
+    type Item = ContentSpan<'a>;
+    type IntoIter = std::vec::IntoIter<ContentSpan<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.spans.into_iter()
+    }
+}
+
+// A segment held by a `ContentComposer`, until its tag is made
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    UserMention(PublicKey),
+    ServerMention(PublicKey),
+    Quote(Reference, Kind),
+    Link(String),
+    Image(String),
+    Video(String),
+}
+
+/// Composes printable content and the content segment tags that go with it,
+/// computing the offsets.
+///
+/// Segments are placed inline between pieces of text. `markup()` also
+/// detects them in plain text. The result reads back with `RichText::new()`.
+///
+/// ```
+/// # use mosaic_core::*;
+/// let alice = SecretKey::generate().public();
+/// let text = format!("Hello {} see https://example.com", alice.as_printable());
+/// let (payload, tag_set) = ContentComposer::new().markup(&text).build().unwrap();
+///
+/// let content = std::str::from_utf8(&payload).unwrap();
+/// assert_eq!(content, "Hello  see ");
+/// assert_eq!(
+///     RichText::new(content, &tag_set).unwrap().spans(),
+///     &[
+///         ContentSpan::Text("Hello "),
+///         ContentSpan::UserMention(alice),
+///         ContentSpan::Text(" see "),
+///         ContentSpan::Link("https://example.com"),
+///     ]
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContentComposer {
+    content: String,
+    segments: Vec<(usize, Segment)>,
+}
+
+impl ContentComposer {
+    /// Create an empty `ContentComposer`
+    #[must_use]
+    pub fn new() -> ContentComposer {
+        ContentComposer::default()
+    }
+
+    /// Append plain text
+    #[must_use]
+    pub fn text(mut self, text: &str) -> ContentComposer {
+        self.content.push_str(text);
+        self
+    }
+
+    /// Append a mention of a user. Mentioned users are notified.
+    #[must_use]
+    pub fn user_mention(self, public_key: PublicKey) -> ContentComposer {
+        self.segment(Segment::UserMention(public_key))
+    }
+
+    /// Append a mention of a server
+    #[must_use]
+    pub fn server_mention(self, public_key: PublicKey) -> ContentComposer {
+        self.segment(Segment::ServerMention(public_key))
+    }
+
+    /// Append a quote of a record of the `Kind`
+    #[must_use]
+    pub fn quote(self, reference: Reference, kind: Kind) -> ContentComposer {
+        self.segment(Segment::Quote(reference, kind))
+    }
+
+    /// Append a link
+    #[must_use]
+    pub fn link(self, url: &str) -> ContentComposer {
+        self.segment(Segment::Link(url.to_owned()))
+    }
+
+    /// Append an image
+    #[must_use]
+    pub fn image(self, url: &str) -> ContentComposer {
+        self.segment(Segment::Image(url.to_owned()))
+    }
+
+    /// Append a video
+    #[must_use]
+    pub fn video(self, url: &str) -> ContentComposer {
+        self.segment(Segment::Video(url.to_owned()))
+    }
+
+    /// Append text, turning printable public keys (`mopub0...`) into user
+    /// mentions, `http` and `https` URLs into links, and references to
+    /// addresses (`moref0...`) into quotes.
+    ///
+    /// A reference to an `Id` does not say what kind of record it refers to,
+    /// so it is left as text; use `quote()` for those.
+    #[must_use]
+    pub fn markup(mut self, text: &str) -> ContentComposer {
+        let mut rest = text;
+        while !rest.is_empty() {
+            // Split off leading whitespace, then a word
+            let word_start = rest
+                .find(|c: char| !c.is_whitespace())
+                .unwrap_or(rest.len());
+            let (space, after) = rest.split_at(word_start);
+            self.content.push_str(space);
+            let word_end = after.find(char::is_whitespace).unwrap_or(after.len());
+            let (word, after) = after.split_at(word_end);
+            rest = after;
+
+            // Surrounding punctuation belongs to the sentence, not the token
+            let token = word
+                .trim_start_matches('(')
+                .trim_end_matches(['.', ',', ';', ':', '!', '?', ')']);
+            let (open, after) = word.split_at(word.len() - word.trim_start_matches('(').len());
+            let (_, close) = after.split_at(token.len());
+            match detect(token) {
+                Some(segment) => {
+                    self.content.push_str(open);
+                    self = self.segment(segment);
+                    self.content.push_str(close);
+                }
+                None => self.content.push_str(word),
+            }
+        }
+        self
+    }
+
+    fn segment(mut self, segment: Segment) -> ContentComposer {
+        self.segments.push((self.content.len(), segment));
+        self
+    }
+
+    /// Produce the payload and the `OwnedTagSet` of content segment tags,
+    /// followed by a `NOTIFY_PUBLIC_KEY` tag for each user mentioned.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if two segments are adjacent with no text between
+    /// them (they would overlap), if an offset does not fit in a tag, or if a
+    /// URL is too long for a tag.
+    pub fn build(self) -> Result<(Vec<u8>, OwnedTagSet), Error> {
+        let mut tag_set = OwnedTagSet::new();
+        let mut notify: Vec<PublicKey> = Vec::new();
+        let mut last_offset: Option<u32> = None;
+        for (offset, segment) in &self.segments {
+            let offset = u32::try_from(*offset)?;
+            if last_offset == Some(offset) {
+                return Err(InnerError::ContentSegmentsOverlap(offset).into());
+            }
+            last_offset = Some(offset);
+            let parsed = match segment {
+                Segment::UserMention(public_key) => {
+                    if !notify.contains(public_key) {
+                        notify.push(*public_key);
+                    }
+                    ParsedTag::UserMention {
+                        public_key: *public_key,
+                        offset,
+                    }
+                }
+                Segment::ServerMention(public_key) => ParsedTag::ServerMention {
+                    public_key: *public_key,
+                    offset,
+                },
+                Segment::Quote(reference, kind) => ParsedTag::Quote {
+                    reference: *reference,
+                    kind: *kind,
+                    offset,
+                },
+                Segment::Link(url) => ParsedTag::Url { url, offset },
+                Segment::Image(url) => ParsedTag::Image { url, offset },
+                Segment::Video(url) => ParsedTag::Video { url, offset },
+            };
+            tag_set.add_tag(&parsed.to_owned_tag()?);
+        }
+        for public_key in &notify {
+            tag_set.add_tag(&OwnedTag::new_notify_public_key(public_key));
+        }
+        Ok((self.content.into_bytes(), tag_set))
+    }
+}
+
+// Detect a segment written as plain text
+fn detect(token: &str) -> Option<Segment> {
+    if token.starts_with("mopub0") {
+        PublicKey::from_printable(token)
+            .ok()
+            .map(Segment::UserMention)
+    } else if token.starts_with("moref0") {
+        let reference = Reference::from_printable(token).ok()?;
+        let kind = reference.as_address().ok()?.kind();
+        Some(Segment::Quote(reference, kind))
+    } else if (token.starts_with("https://") && token.len() > 8)
+        || (token.starts_with("http://") && token.len() > 7)
+    {
+        Some(Segment::Link(token.to_owned()))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn test_rich_text() {
+        let alice = SecretKey::generate().public();
+        let server = SecretKey::generate().public();
+        let reference = Reference::from_printable(
+            "moref01ge91q91o36bcfrk7qfhpnydyyobh88zknproi8j5791e5mekfez1ye6zrifbhh6m1dtizcsp4y5w",
+        )
+        .unwrap();
+        let content = "Hi  — see  or .";
+
+        // Tags need not be in offset order
+        let mut tag_set = OwnedTagSet::new();
+        tag_set.add_tag(&OwnedTag::new_content_segment_image(
+            "https://x.com/a.png",
+            17,
+        ));
+        tag_set.add_tag(&OwnedTag::new_notify_public_key(&alice));
+        tag_set.add_tag(&OwnedTag::new_content_segment_user_mention(&alice, 3));
+        tag_set.add_tag(&OwnedTag::new_content_segment_quote(
+            &reference,
+            Kind::MICROBLOG_ROOT,
+            12,
+        ));
+        tag_set.add_tag(&OwnedTag::new_content_segment_url("https://x.com", 16));
+        tag_set.add_tag(&OwnedTag::new_content_segment_server_mention(&server, 0));
+
+        let rich_text = RichText::new(content, &tag_set).unwrap();
+        assert_eq!(
+            rich_text.into_spans(),
+            vec![
+                ContentSpan::ServerMention(server),
+                ContentSpan::Text("Hi "),
+                ContentSpan::UserMention(alice),
+                ContentSpan::Text(" — see "),
+                ContentSpan::Quote(reference, Kind::MICROBLOG_ROOT),
+                ContentSpan::Text(" or "),
+                ContentSpan::Link("https://x.com"),
+                ContentSpan::Text("."),
+                ContentSpan::Image("https://x.com/a.png"),
+            ]
+        );
+
+        // No segments
+        assert_eq!(
+            RichText::new("plain", &OwnedTagSet::new()).unwrap().spans(),
+            &[ContentSpan::Text("plain")]
+        );
+
+        let check_err = |offsets: &[u32], expected: InnerError| {
+            let mut tag_set = OwnedTagSet::new();
+            for offset in offsets {
+                tag_set.add_tag(&OwnedTag::new_content_segment_user_mention(&alice, *offset));
+            }
+            let e = RichText::new(content, &tag_set).unwrap_err();
+            assert_eq!(e.inner.to_string(), expected.to_string());
+        };
+        check_err(&[18], InnerError::ContentSegmentOutOfRange(18));
+        check_err(&[5], InnerError::ContentSegmentNotOnCharBoundary(5));
+        check_err(&[3, 3], InnerError::ContentSegmentsOverlap(3));
+    }
+
+    #[test]
+    fn test_content_composer() {
+        let alice = SecretKey::generate().public();
+        let server = SecretKey::generate().public();
+        let address = Address::new_random(alice, Kind::BLOG_POST);
+        let id_reference = Id::from_parts(
+            &[1; 40],
+            Timestamp::from_unixtime(1_700_000_000, 0).unwrap(),
+        )
+        .to_reference();
+
+        let text = format!(
+            "Hey {}, read {} (https://example.com/post). Not {} or http://",
+            alice.as_printable(),
+            address.to_reference().as_printable(),
+            id_reference.as_printable(),
+        );
+        let (payload, tag_set) = ContentComposer::new()
+            .server_mention(server)
+            .markup(&text)
+            .text(" ")
+            .image("https://example.com/a.png")
+            .text(" bye ")
+            .user_mention(alice)
+            .build()
+            .unwrap();
+        let content = std::str::from_utf8(&payload).unwrap();
+
+        // Round trips with RichText
+        let not_detected = format!("). Not {} or http:// ", id_reference.as_printable());
+        assert_eq!(
+            RichText::new(content, &tag_set).unwrap().into_spans(),
+            vec![
+                ContentSpan::ServerMention(server),
+                ContentSpan::Text("Hey "),
+                ContentSpan::UserMention(alice),
+                ContentSpan::Text(", read "),
+                ContentSpan::Quote(address.to_reference(), Kind::BLOG_POST),
+                ContentSpan::Text(" ("),
+                ContentSpan::Link("https://example.com/post"),
+                ContentSpan::Text(&not_detected),
+                ContentSpan::Image("https://example.com/a.png"),
+                ContentSpan::Text(" bye "),
+                ContentSpan::UserMention(alice),
+            ]
+        );
+
+        // One notification per user
+        let notified: Vec<PublicKey> = tag_set
+            .iter()
+            .filter(|tag| tag.get_type() == TagType::NOTIFY_PUBLIC_KEY)
+            .map(|tag| tag.get_public_key().unwrap().unwrap())
+            .collect();
+        assert_eq!(notified, vec![alice]);
+
+        // Adjacent segments would overlap
+        let e = ContentComposer::new()
+            .link("https://example.com")
+            .image("https://example.com/a.png")
+            .build()
+            .unwrap_err();
+        assert!(matches!(e.inner, InnerError::ContentSegmentsOverlap(0)));
+    }
+}
@@ -25,6 +25,9 @@ impl std::fmt::Display for Error {
 /// Errors that can occur in this crate
 #[derive(Debug)]
 pub enum InnerError {
+    /// Address does not match
+    AddressMismatch,
+
     /// Bad Encrypted Secret Key
     BadEncryptedSecretKey,
 
@@ -40,12 +43,27 @@ pub enum InnerError {
     /// CBOR cannot be decoded
     CborDecode(Box<minicbor::decode::Error>),
 
+    /// Payload is compressed but compression support is not compiled in
+    CompressionNotSupported,
+
+    /// A content segment offset is not on a UTF-8 character boundary
+    ContentSegmentNotOnCharBoundary(u32),
+
+    /// A content segment offset is beyond the end of the content
+    ContentSegmentOutOfRange(u32),
+
+    /// Content segments overlap (share an offset)
+    ContentSegmentsOverlap(u32),
+
     /// Data too long
     DataTooLong,
 
     /// Data too short
     DataTooShort,
 
+    /// Decompressed payload exceeds the maximum length
+    DecompressedPayloadTooLong(usize),
+
     /// DHT put error
     DhtPutError,
 
@@ -67,12 +85,18 @@ pub enum InnerError {
     /// Filter element is too long
     FilterElementTooLong,
 
+    /// Filter is too long
+    FilterTooLong,
+
     /// Hash mismatch
     HashMismatch,
 
     /// Key data length is not 32 bytes
     KeyLength,
 
+    /// Kind is defined by this crate or already registered
+    KindInUse(crate::Kind),
+
     /// General error
     General(String),
 
@@ -82,6 +106,9 @@ pub enum InnerError {
     /// Invalid Address bytes
     InvalidAddressBytes,
 
+    /// Invalid archive
+    InvalidArchive,
+
     /// Invalid filter element
     InvalidFilterElement,
 
@@ -97,6 +124,10 @@ pub enum InnerError {
     /// Invalid message
     InvalidMessage,
 
+    /// Invalid Nostr event
+    #[cfg(feature = "nostr")]
+    InvalidNostrEvent,
+
     /// Invalid printable data
     InvalidPrintable,
 
@@ -118,6 +149,9 @@ pub enum InnerError {
     /// Invalid URI parts
     InvalidUriParts(http::uri::InvalidUriParts),
 
+    /// I/O error
+    Io(std::io::Error),
+
     /// Json Error
     #[cfg(feature = "json")]
     Json(serde_json::Error),
@@ -129,6 +163,12 @@ pub enum InnerError {
     /// Missing scheme
     MissingScheme,
 
+    /// Signing data cannot produce a signature
+    MissingSignature,
+
+    /// A required tag is missing
+    MissingTag(crate::TagType),
+
     /// Reference is not an Address
     NotAnAddress,
 
@@ -165,6 +205,12 @@ pub enum InnerError {
     /// Scrypt error
     Scrypt(scrypt::errors::InvalidParams),
 
+    /// secp256k1 error
+    Secp256k1(k256::schnorr::Error),
+
+    /// A `Signer` produced a signature in a different scheme than it declared
+    SignerSchemeMismatch,
+
     /// Slice error
     SliceError(std::array::TryFromSliceError),
 
@@ -174,9 +220,15 @@ pub enum InnerError {
     /// Time error
     SystemTime(std::time::SystemTimeError),
 
+    /// Tag set too long
+    TagSetTooLong,
+
     /// Tag too long
     TagTooLong,
 
+    /// Tag type is defined by this crate or already registered
+    TagTypeInUse(crate::TagType),
+
     /// Time is beyond available leap second data
     TimeIsBeyondLeapSecondData,
 
@@ -189,15 +241,32 @@ pub enum InnerError {
     /// Too many data elements
     TooManyDataElements(usize),
 
+    /// Stream ended partway through a record
+    TruncatedRecord,
+
+    /// The signing key is not authorized to sign for the author
+    UnauthorizedSigningKey,
+
     /// Undefined Subkey Marker
     UndefinedSubkeyMarker(u16),
 
     /// Unknown filter element
     UnknownFilterElement(u8),
 
+    /// Nostr key is not mapped to a Mosaic key
+    #[cfg(feature = "nostr")]
+    UnmappedNostrKey,
+
     /// Unsupported Encrypted Secret Key Version
     UnsupportedEncryptedSecretKeyVersion(u8),
 
+    /// Unsupported Nostr event kind
+    #[cfg(feature = "nostr")]
+    UnsupportedNostrKind(u32),
+
+    /// Unsupported signature scheme
+    UnsupportedSignatureScheme(crate::SignatureScheme),
+
     /// UTF-8 error
     Utf8(std::str::Utf8Error),
 
@@ -212,15 +281,34 @@ pub enum InnerError {
 }
 
 impl std::fmt::Display for InnerError {
+    #[allow(clippy::too_many_lines)]
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            InnerError::AddressMismatch => write!(f, "Address does not match"),
             InnerError::BadEncryptedSecretKey => write!(f, "Bad encrypted secret key"),
             InnerError::BadIndex => write!(f, "Bad index"),
             InnerError::BadPassword => write!(f, "Bad password"),
             InnerError::BadScheme(s) => write!(f, "Unsupported URI scheme: {s}"),
             InnerError::CborDecode(e) => write!(f, "CBOR can't be decoded: {e}"),
+            InnerError::CompressionNotSupported => write!(
+                f,
+                "Payload is compressed but the zstd feature is not enabled"
+            ),
+            InnerError::ContentSegmentNotOnCharBoundary(offset) => write!(
+                f,
+                "Content segment offset {offset} is not on a character boundary"
+            ),
+            InnerError::ContentSegmentOutOfRange(offset) => {
+                write!(f, "Content segment offset {offset} is beyond the content")
+            }
+            InnerError::ContentSegmentsOverlap(offset) => {
+                write!(f, "Content segments overlap at offset {offset}")
+            }
             InnerError::DataTooLong => write!(f, "Data too long"),
             InnerError::DataTooShort => write!(f, "Data too short"),
+            InnerError::DecompressedPayloadTooLong(max) => {
+                write!(f, "Decompressed payload too long. Max is {max}")
+            }
             InnerError::DhtPutError => write!(f, "DHT put error"),
             InnerError::DhtWasShutdown => write!(f, "DHT was shutdown"),
             InnerError::Ed25519(e) => write!(f, "ed25519 Error: {e}"),
@@ -230,11 +318,14 @@ impl std::fmt::Display for InnerError {
                 write!(f, "Computationally excessive scrypt LOG_N parameter: {l}")
             }
             InnerError::FilterElementTooLong => write!(f, "Filter element too long"),
+            InnerError::FilterTooLong => write!(f, "Filter too long"),
             InnerError::HashMismatch => write!(f, "Hash mismatch"),
             InnerError::KeyLength => write!(f, "Key data length is not 32 bytes"),
+            InnerError::KindInUse(k) => write!(f, "Kind {:x} is already in use", k.to_u64()),
             InnerError::General(s) => write!(f, "General Error: {s}"),
             InnerError::IntTooBig(e) => write!(f, "Integer too big: {e}"),
             InnerError::InvalidAddressBytes => write!(f, "Invalid Address bytes"),
+            InnerError::InvalidArchive => write!(f, "Invalid archive"),
             InnerError::InvalidFilterElement => write!(f, "Invalid filter element"),
             InnerError::InvalidFilterElementForFunction => write!(
                 f,
@@ -243,6 +334,8 @@ impl std::fmt::Display for InnerError {
             InnerError::InvalidIdBytes => write!(f, "Invalid ID bytes"),
             InnerError::InvalidLength => write!(f, "Invalid length"),
             InnerError::InvalidMessage => write!(f, "Invalid message"),
+            #[cfg(feature = "nostr")]
+            InnerError::InvalidNostrEvent => write!(f, "Invalid Nostr event"),
             InnerError::InvalidPrintable => write!(f, "Printable data is invalid"),
             InnerError::InvalidResultCode => write!(f, "Invalid result code"),
             InnerError::InvalidServerBootstrapString => write!(f, "Invalid ServerBootstrap String"),
@@ -250,11 +343,16 @@ impl std::fmt::Display for InnerError {
             InnerError::InvalidUserBootstrapString => write!(f, "Invalid UserBootstrap String"),
             InnerError::InvalidUri(e) => write!(f, "Invalid URI: {e}"),
             InnerError::InvalidUriParts(e) => write!(f, "Invalid URI parts: {e}"),
+            InnerError::Io(e) => write!(f, "I/O error: {e}"),
             #[cfg(feature = "json")]
             InnerError::Json(e) => write!(f, "JSON: {e}"),
             #[cfg(feature = "json")]
             InnerError::JsonIdIsIncorrect => write!(f, "JSON ID is incorrect"),
             InnerError::MissingScheme => write!(f, "Missing scheme"),
+            InnerError::MissingSignature => {
+                write!(f, "Signing data has no key or signature to sign with")
+            }
+            InnerError::MissingTag(t) => write!(f, "Missing {t} tag"),
             InnerError::NotAnAddress => write!(f, "Reference is not an address"),
             InnerError::NotAnId => write!(f, "Reference is not an ID"),
             InnerError::NotFound => write!(f, "Not found"),
@@ -267,23 +365,43 @@ impl std::fmt::Display for InnerError {
             InnerError::ReservedFlagsUsed => write!(f, "Reserved flags used"),
             InnerError::ReservedSpaceUsed => write!(f, "Reserved space used"),
             InnerError::Scrypt(e) => write!(f, "Scrypt: {e}"),
+            InnerError::Secp256k1(e) => write!(f, "secp256k1 Error: {e}"),
+            InnerError::SignerSchemeMismatch => {
+                write!(f, "Signer produced a signature in an unexpected scheme")
+            }
             InnerError::SliceError(e) => write!(f, "Slice (size) error: {e}"),
             InnerError::SubkeyMarkerRequiresATimestamp => {
                 write!(f, "SubkeyMarker requires a (non zero) Timestamp")
             }
             InnerError::SystemTime(e) => write!(f, "Time Error: {e}"),
+            InnerError::TagSetTooLong => write!(f, "Tag set too long"),
             InnerError::TagTooLong => write!(f, "Tag too long"),
+            InnerError::TagTypeInUse(t) => write!(f, "Tag type {} is already in use", t.0),
             InnerError::TimeIsBeyondLeapSecondData => {
                 write!(f, "Time is beyond available leap second data")
             }
             InnerError::TimeOutOfRange => write!(f, "Time is out of range"),
             InnerError::TimestampMismatch => write!(f, "Timestamp mismatch"),
             InnerError::TooManyDataElements(c) => write!(f, "Too many data elements. Max is {c}"),
+            InnerError::TruncatedRecord => write!(f, "Stream ended partway through a record"),
+            InnerError::UnauthorizedSigningKey => {
+                write!(
+                    f,
+                    "Signing key is not authorized by the author's key schedule"
+                )
+            }
             InnerError::UndefinedSubkeyMarker(u) => write!(f, "Undefined Subkey Marker: {u}"),
             InnerError::UnknownFilterElement(u) => write!(f, "Unknown filter element: {u}"),
+            #[cfg(feature = "nostr")]
+            InnerError::UnmappedNostrKey => write!(f, "Nostr key is not mapped to a Mosaic key"),
             InnerError::UnsupportedEncryptedSecretKeyVersion(v) => {
                 write!(f, "Unsupported Encrypted Secret Key Version: {v}")
             }
+            #[cfg(feature = "nostr")]
+            InnerError::UnsupportedNostrKind(k) => write!(f, "Unsupported Nostr event kind: {k}"),
+            InnerError::UnsupportedSignatureScheme(s) => {
+                write!(f, "Unsupported signature scheme: {s:?}")
+            }
             InnerError::Utf8(e) => write!(f, "UTF-8 error: {e}"),
             InnerError::WrongKind => write!(f, "Wrong kind"),
             InnerError::WrongLength => write!(f, "Wrong length"),
@@ -300,10 +418,12 @@ impl StdError for InnerError {
             InnerError::IntTooBig(e) => Some(e),
             InnerError::InvalidUri(e) => Some(e),
             InnerError::InvalidUriParts(e) => Some(e),
+            InnerError::Io(e) => Some(e),
             #[cfg(feature = "json")]
             InnerError::Json(e) => Some(e),
             InnerError::ParseInt(e) => Some(e),
             InnerError::Scrypt(e) => Some(e),
+            InnerError::Secp256k1(e) => Some(e),
             InnerError::SliceError(e) => Some(e),
             InnerError::SystemTime(e) => Some(e),
             InnerError::Utf8(e) => Some(e),
@@ -435,6 +555,16 @@ impl From<http::uri::InvalidUriParts> for Error {
     }
 }
 
+impl From<std::io::Error> for Error {
+    #[track_caller]
+    fn from(e: std::io::Error) -> Error {
+        Error {
+            inner: InnerError::Io(e),
+            location: Location::caller(),
+        }
+    }
+}
+
 #[cfg(feature = "json")]
 impl From<serde_json::Error> for Error {
     #[track_caller]
@@ -0,0 +1,341 @@
+use super::{OwnedFilter, OwnedFilterElement};
+use crate::{Error, Id, InnerError, Kind, OwnedTag, PublicKey, Tag, Timestamp};
+
+// Capacities of the list filter elements
+const MAX_KEYS: usize = 63;
+const MAX_KINDS: usize = 254;
+const MAX_TIMESTAMPS: usize = 254;
+const MAX_IDS: usize = 63;
+const MAX_TAGS_LEN: usize = 254 * 8;
+
+// The length of a filter is a u16
+const MAX_FILTER_LEN: usize = 65_535;
+
+/// A builder for `OwnedFilter`s
+///
+/// Lists longer than a `FilterElement` can hold are split across several
+/// filters, which together match what the one filter would have. A record
+/// matches if it matches any of them.
+///
+/// ```
+/// # use mosaic_core::*;
+/// let follows: Vec<PublicKey> = (0..200).map(|_| SecretKey::generate().public()).collect();
+/// let filters = FilterBuilder::new()
+///     .authors(follows)
+///     .kinds([Kind::MICROBLOG_ROOT, Kind::REPLY_COMMENT])
+///     .since(Timestamp::from_unixtime(1_700_000_000, 0).unwrap())
+///     .build()
+///     .unwrap();
+/// assert_eq!(filters.len(), 4);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FilterBuilder {
+    authors: Vec<PublicKey>,
+    signing_keys: Vec<PublicKey>,
+    kinds: Vec<Kind>,
+    timestamps: Vec<Timestamp>,
+    tags: Vec<OwnedTag>,
+    since: Option<Timestamp>,
+    until: Option<Timestamp>,
+    received_since: Option<Timestamp>,
+    received_until: Option<Timestamp>,
+    exclude: Vec<Id>,
+    excluded_tags: Vec<OwnedTag>,
+}
+
+impl FilterBuilder {
+    /// Start building a filter that matches everything
+    #[must_use]
+    pub fn new() -> FilterBuilder {
+        FilterBuilder::default()
+    }
+
+    /// Match records by any of these authors
+    #[must_use]
+    pub fn authors<I: IntoIterator<Item = PublicKey>>(mut self, authors: I) -> FilterBuilder {
+        self.authors.extend(authors);
+        self
+    }
+
+    /// Match records signed by any of these keys
+    #[must_use]
+    pub fn signing_keys<I: IntoIterator<Item = PublicKey>>(mut self, keys: I) -> FilterBuilder {
+        self.signing_keys.extend(keys);
+        self
+    }
+
+    /// Match records of any of these kinds
+    #[must_use]
+    pub fn kinds<I: IntoIterator<Item = Kind>>(mut self, kinds: I) -> FilterBuilder {
+        self.kinds.extend(kinds);
+        self
+    }
+
+    /// Match records with any of these exact timestamps
+    #[must_use]
+    pub fn timestamps<I: IntoIterator<Item = Timestamp>>(mut self, timestamps: I) -> FilterBuilder {
+        self.timestamps.extend(timestamps);
+        self
+    }
+
+    /// Match records with any of these tags
+    #[must_use]
+    pub fn tags<I, T>(mut self, tags: I) -> FilterBuilder
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<Tag>,
+    {
+        self.tags
+            .extend(tags.into_iter().map(|t| t.as_ref().to_owned()));
+        self
+    }
+
+    /// Match records at or after this time
+    #[must_use]
+    pub fn since(mut self, since: Timestamp) -> FilterBuilder {
+        self.since = Some(since);
+        self
+    }
+
+    /// Match records at or before this time
+    #[must_use]
+    pub fn until(mut self, until: Timestamp) -> FilterBuilder {
+        self.until = Some(until);
+        self
+    }
+
+    /// Match records the server received at or after this time
+    #[must_use]
+    pub fn received_since(mut self, since: Timestamp) -> FilterBuilder {
+        self.received_since = Some(since);
+        self
+    }
+
+    /// Match records the server received at or before this time
+    #[must_use]
+    pub fn received_until(mut self, until: Timestamp) -> FilterBuilder {
+        self.received_until = Some(until);
+        self
+    }
+
+    /// Do not match these records
+    #[must_use]
+    pub fn exclude<I: IntoIterator<Item = Id>>(mut self, ids: I) -> FilterBuilder {
+        self.exclude.extend(ids);
+        self
+    }
+
+    /// Do not match records with any of these tags
+    #[must_use]
+    pub fn excluded_tags<I, T>(mut self, tags: I) -> FilterBuilder
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<Tag>,
+    {
+        self.excluded_tags
+            .extend(tags.into_iter().map(|t| t.as_ref().to_owned()));
+        self
+    }
+
+    /// Build the filters. There is one unless a list had to be split, in
+    /// which case there is one for each combination of the parts.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if a single tag is too long for a filter element, or
+    /// the exclusions are too many to fit in a filter (they must be in every
+    /// one).
+    pub fn build(&self) -> Result<Vec<OwnedFilter>, Error> {
+        // Alternatives: each filter has one element from each of these
+        let alternatives = [
+            chunks(&self.authors, MAX_KEYS, OwnedFilterElement::new_author_keys)?,
+            chunks(
+                &self.signing_keys,
+                MAX_KEYS,
+                OwnedFilterElement::new_signing_keys,
+            )?,
+            chunks(&self.kinds, MAX_KINDS, OwnedFilterElement::new_kinds)?,
+            chunks(
+                &self.timestamps,
+                MAX_TIMESTAMPS,
+                OwnedFilterElement::new_timestamps,
+            )?,
+            tag_chunks(&self.tags, OwnedFilterElement::new_included_tags)?,
+        ];
+
+        // Restrictions: every filter has all of these
+        let mut restrictions: Vec<OwnedFilterElement> = Vec::new();
+        restrictions.extend(self.since.map(OwnedFilterElement::new_since));
+        restrictions.extend(self.until.map(OwnedFilterElement::new_until));
+        restrictions.extend(
+            self.received_since
+                .map(OwnedFilterElement::new_received_since),
+        );
+        restrictions.extend(
+            self.received_until
+                .map(OwnedFilterElement::new_received_until),
+        );
+        restrictions.extend(chunks(
+            &self.exclude,
+            MAX_IDS,
+            OwnedFilterElement::new_exclude,
+        )?);
+        restrictions.extend(tag_chunks(
+            &self.excluded_tags,
+            OwnedFilterElement::new_excluded_tags,
+        )?);
+
+        let mut combinations: Vec<Vec<&OwnedFilterElement>> = vec![Vec::new()];
+        for elements in alternatives.iter().filter(|e| !e.is_empty()) {
+            combinations = combinations
+                .iter()
+                .flat_map(|combination| {
+                    elements.iter().map(move |element| {
+                        let mut combination = combination.clone();
+                        combination.push(element);
+                        combination
+                    })
+                })
+                .collect();
+        }
+
+        combinations
+            .into_iter()
+            .map(|mut elements| {
+                elements.extend(restrictions.iter());
+                let len: usize = 8 + elements.iter().map(|e| e.as_bytes().len()).sum::<usize>();
+                if len > MAX_FILTER_LEN {
+                    return Err(InnerError::FilterTooLong.into());
+                }
+                OwnedFilter::new(&elements)
+            })
+            .collect()
+    }
+}
+
+// Filter elements of the items, at most `size` in each
+fn chunks<T>(
+    items: &[T],
+    size: usize,
+    new: fn(&[T]) -> Result<OwnedFilterElement, Error>,
+) -> Result<Vec<OwnedFilterElement>, Error> {
+    items.chunks(size).map(new).collect()
+}
+
+// Filter elements of the tags, as many in each as fit
+fn tag_chunks(
+    tags: &[OwnedTag],
+    new: fn(&[OwnedTag]) -> Result<OwnedFilterElement, Error>,
+) -> Result<Vec<OwnedFilterElement>, Error> {
+    let mut elements: Vec<OwnedFilterElement> = Vec::new();
+    let mut start = 0;
+    let mut len = 0;
+    for (i, tag) in tags.iter().enumerate() {
+        let tag_len = tag.as_bytes().len();
+        if len + tag_len > MAX_TAGS_LEN && i > start {
+            elements.push(new(&tags[start..i])?);
+            start = i;
+            len = 0;
+        }
+        len += tag_len;
+    }
+    if start < tags.len() {
+        elements.push(new(&tags[start..])?);
+    }
+    Ok(elements)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn test_filter_builder() {
+        let secret_key = SecretKey::generate();
+        let authors: Vec<PublicKey> = (0..130)
+            .map(|i| {
+                if i == 129 {
+                    secret_key.public()
+                } else {
+                    SecretKey::generate().public()
+                }
+            })
+            .collect();
+        let timestamp = Timestamp::from_unixtime(1_700_000_000, 0).unwrap();
+
+        // Nothing is everything
+        let filters = FilterBuilder::new().build().unwrap();
+        assert_eq!(filters.len(), 1);
+        assert_eq!(filters[0].elements().count(), 0);
+
+        let hashtags: Vec<OwnedTag> = (0..200)
+            .map(|i| OwnedTag::new_hashtag(&format!("tag{i}")).unwrap())
+            .collect();
+        let filters = FilterBuilder::new()
+            .authors(authors.clone())
+            .kinds([Kind::MICROBLOG_ROOT])
+            .tags(&hashtags)
+            .since(timestamp)
+            .build()
+            .unwrap();
+
+        // 3 chunks of authors, and 2 of tags
+        assert_eq!(filters.len(), 6);
+        for filter in &filters {
+            assert!(filter.get_element(FilterElementType::KINDS).is_some());
+            assert!(filter.get_element(FilterElementType::SINCE).is_some());
+            assert!(filter.get_element(FilterElementType::AUTHOR_KEYS).is_some());
+            assert!(filter
+                .get_element(FilterElementType::INCLUDED_TAGS)
+                .is_some());
+            let _ = Filter::from_bytes(filter.as_bytes()).unwrap();
+        }
+        let mut author_count = 0;
+        for filter in filters.iter().step_by(2) {
+            let element = filter.get_element(FilterElementType::AUTHOR_KEYS).unwrap();
+            author_count += element.keys().unwrap().count();
+        }
+        assert_eq!(author_count, 130);
+
+        // A record matches exactly one of them
+        let record = RecordBuilder::new(Kind::MICROBLOG_ROOT, secret_key)
+            .timestamp(timestamp)
+            .tag(&hashtags[150])
+            .unwrap()
+            .sign()
+            .unwrap();
+        let matching = filters
+            .iter()
+            .filter(|f| f.matches(&record).unwrap())
+            .count();
+        assert_eq!(matching, 1);
+    }
+
+    #[test]
+    fn test_filter_builder_limits() {
+        let ids: Vec<Id> = (0..2_100_u16)
+            .map(|i| {
+                let mut bytes = [0_u8; 48];
+                bytes[0..2].copy_from_slice(&i.to_le_bytes());
+                Id::from_parts(
+                    &bytes[..40].try_into().unwrap(),
+                    Timestamp::from_unixtime(1_700_000_000, 0).unwrap(),
+                )
+            })
+            .collect();
+
+        // Exclusions go in every filter, so must all fit in one
+        let filters = FilterBuilder::new()
+            .exclude(ids[..100].to_vec())
+            .build()
+            .unwrap();
+        assert_eq!(filters.len(), 1);
+        assert_eq!(filters[0].elements().count(), 2);
+        let e = FilterBuilder::new().exclude(ids).build().unwrap_err();
+        assert!(matches!(e.inner, InnerError::FilterTooLong));
+
+        let tag = OwnedTag::new(TagType(100), &[0; 2_100]).unwrap();
+        let e = FilterBuilder::new().tags([&tag]).build().unwrap_err();
+        assert!(matches!(e.inner, InnerError::FilterElementTooLong));
+    }
+}
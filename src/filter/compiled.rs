@@ -0,0 +1,256 @@
+use super::{Filter, FilterElementType, MatchContext};
+use crate::{Error, InnerError, Kind, PublicKey, Record, Timestamp};
+use std::collections::HashSet;
+
+// A list filter element, of which a record must match at least one entry
+#[derive(Debug, Clone)]
+enum AnyOf {
+    AuthorKeys(HashSet<PublicKey>),
+    SigningKeys(HashSet<[u8; 32]>),
+    Kinds(HashSet<Kind>),
+    Timestamps(Vec<Timestamp>),
+    Tags(HashSet<Vec<u8>>),
+}
+
+impl AnyOf {
+    fn matches(&self, record: &Record) -> bool {
+        match self {
+            AnyOf::AuthorKeys(keys) => keys.contains(&record.author_public_key()),
+            AnyOf::SigningKeys(keys) => keys.contains(record.signing_key_bytes()),
+            AnyOf::Kinds(kinds) => kinds.contains(&record.kind()),
+            AnyOf::Timestamps(timestamps) => timestamps.binary_search(&record.timestamp()).is_ok(),
+            AnyOf::Tags(tags) => record
+                .tag_set()
+                .iter()
+                .any(|tag| tags.contains(tag.as_bytes())),
+        }
+    }
+}
+
+/// A `Filter` parsed ahead of time, for matching many records quickly
+///
+/// Keys, kinds and tags are held in hash sets and timestamps in sorted
+/// vectors, so matching a record takes time independent of the length of
+/// the filter's lists, and does not allocate.
+///
+/// Like `Filter::matches()`, `matches()` ignores `ReceivedSince` and
+/// `ReceivedUntil`, which are not properties of the record;
+/// `matches_with_context()` evaluates them.
+#[derive(Debug, Clone)]
+pub struct CompiledFilter {
+    any_of: Vec<AnyOf>,
+    since: Option<Timestamp>,
+    until: Option<Timestamp>,
+    received_since: Option<Timestamp>,
+    received_until: Option<Timestamp>,
+    excluded_ids: HashSet<[u8; 32]>,
+    excluded_tags: HashSet<Vec<u8>>,
+}
+
+impl Filter {
+    /// Parse the filter for fast matching
+    ///
+    /// # Errors
+    ///
+    /// Throws an error on any unknown or malformed `FilterElement`
+    #[allow(clippy::missing_panics_doc)]
+    pub fn compile(&self) -> Result<CompiledFilter, Error> {
+        let mut compiled = CompiledFilter {
+            any_of: Vec::new(),
+            since: None,
+            until: None,
+            received_since: None,
+            received_until: None,
+            excluded_ids: HashSet::new(),
+            excluded_tags: HashSet::new(),
+        };
+
+        for element in self.elements() {
+            match element.get_type() {
+                FilterElementType::AUTHOR_KEYS => compiled
+                    .any_of
+                    .push(AnyOf::AuthorKeys(element.keys().unwrap().collect())),
+                FilterElementType::SIGNING_KEYS => compiled.any_of.push(AnyOf::SigningKeys(
+                    element.keys().unwrap().map(PublicKey::to_bytes).collect(),
+                )),
+                FilterElementType::KINDS => compiled
+                    .any_of
+                    .push(AnyOf::Kinds(element.kinds().unwrap().collect())),
+                FilterElementType::TIMESTAMPS => {
+                    let mut timestamps: Vec<Timestamp> = element.timestamps().unwrap().collect();
+                    timestamps.sort_unstable();
+                    compiled.any_of.push(AnyOf::Timestamps(timestamps));
+                }
+                FilterElementType::INCLUDED_TAGS => compiled.any_of.push(AnyOf::Tags(
+                    element
+                        .tags()
+                        .unwrap()
+                        .map(|tag| tag.as_bytes().to_vec())
+                        .collect(),
+                )),
+                FilterElementType::SINCE => {
+                    let since = element.since()?.unwrap();
+                    compiled.since = Some(compiled.since.map_or(since, |s| s.max(since)));
+                }
+                FilterElementType::UNTIL => {
+                    let until = element.until()?.unwrap();
+                    compiled.until = Some(compiled.until.map_or(until, |u| u.min(until)));
+                }
+                FilterElementType::RECEIVED_SINCE => {
+                    let since = element.since()?.unwrap();
+                    compiled.received_since =
+                        Some(compiled.received_since.map_or(since, |s| s.max(since)));
+                }
+                FilterElementType::RECEIVED_UNTIL => {
+                    let until = element.until()?.unwrap();
+                    compiled.received_until =
+                        Some(compiled.received_until.map_or(until, |u| u.min(until)));
+                }
+                FilterElementType::EXCLUDE => {
+                    compiled.excluded_ids.extend(element.ids().unwrap());
+                }
+                FilterElementType::EXCLUDED_TAGS => compiled
+                    .excluded_tags
+                    .extend(element.tags().unwrap().map(|tag| tag.as_bytes().to_vec())),
+                FilterElementType(u) => return Err(InnerError::UnknownFilterElement(u).into()),
+            }
+        }
+
+        // Check the cheapest elements first
+        compiled.any_of.sort_by_key(|any_of| match any_of {
+            AnyOf::Kinds(_) => 0,
+            AnyOf::AuthorKeys(_) | AnyOf::SigningKeys(_) => 1,
+            AnyOf::Timestamps(_) => 2,
+            AnyOf::Tags(_) => 3,
+        });
+
+        Ok(compiled)
+    }
+}
+
+impl CompiledFilter {
+    /// Does this filter match a given record?
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn matches(&self, record: &Record) -> bool {
+        let timestamp = record.timestamp();
+        if self.since.is_some_and(|since| timestamp < since)
+            || self.until.is_some_and(|until| timestamp > until)
+        {
+            return false;
+        }
+
+        if !self.any_of.iter().all(|any_of| any_of.matches(record)) {
+            return false;
+        }
+
+        if !self.excluded_ids.is_empty() {
+            let (id, address) = (record.id(), record.address());
+            let id: &[u8; 32] = id.as_bytes()[..32].try_into().unwrap();
+            let address: &[u8; 32] = address.as_bytes()[..32].try_into().unwrap();
+            if self.excluded_ids.contains(id) || self.excluded_ids.contains(address) {
+                return false;
+            }
+        }
+
+        !record
+            .tag_set()
+            .iter()
+            .any(|tag| self.excluded_tags.contains(tag.as_bytes()))
+    }
+
+    /// Does this filter match a given record, as received by a server and
+    /// requested by a client?
+    #[must_use]
+    pub fn matches_with_context(&self, record: &Record, context: &MatchContext) -> bool {
+        let received_at = context.received_at;
+        if self.received_since.is_some_and(|since| received_at < since)
+            || self.received_until.is_some_and(|until| received_at > until)
+        {
+            return false;
+        }
+
+        self.matches(record)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use std::time::Duration;
+
+    // Records by a few authors, with a few kinds and tags
+    fn records(timestamp: Timestamp) -> (Vec<SecretKey>, Vec<OwnedTag>, Vec<OwnedRecord>) {
+        let keys: Vec<SecretKey> = (0..4).map(|_| SecretKey::generate()).collect();
+        let hashtags: Vec<OwnedTag> = (0..4)
+            .map(|i| OwnedTag::new_hashtag(&format!("tag{i}")).unwrap())
+            .collect();
+        let kinds = [
+            Kind::MICROBLOG_ROOT,
+            Kind::REPLY_COMMENT,
+            Kind::CHAT_MESSAGE,
+        ];
+        let mut records = Vec::new();
+        for i in 0..48 {
+            records.push(
+                RecordBuilder::new(kinds[i % 3], keys[i % 4].clone())
+                    .timestamp(timestamp + Duration::from_secs(i as u64))
+                    .tag(&hashtags[i / 12])
+                    .unwrap()
+                    .sign()
+                    .unwrap(),
+            );
+        }
+        (keys, hashtags, records)
+    }
+
+    #[test]
+    fn test_compiled_filter() {
+        let timestamp = Timestamp::from_unixtime(1_700_000_000, 0).unwrap();
+        let (keys, hashtags, records) = records(timestamp);
+
+        let filters = [
+            FilterBuilder::new().build().unwrap(),
+            FilterBuilder::new()
+                .authors([keys[0].public(), keys[2].public()])
+                .kinds([Kind::MICROBLOG_ROOT, Kind::CHAT_MESSAGE])
+                .build()
+                .unwrap(),
+            FilterBuilder::new()
+                .signing_keys([keys[1].public()])
+                .tags([&hashtags[1], &hashtags[3]])
+                .since(timestamp + Duration::from_secs(10))
+                .until(timestamp + Duration::from_secs(40))
+                .build()
+                .unwrap(),
+            FilterBuilder::new()
+                .timestamps([
+                    timestamp + Duration::from_secs(30),
+                    timestamp + Duration::from_secs(3),
+                    timestamp + Duration::from_secs(20),
+                ])
+                .build()
+                .unwrap(),
+            FilterBuilder::new()
+                .exclude([records[20].id(), records[30].id()])
+                .excluded_tags([&hashtags[0]])
+                .received_since(timestamp)
+                .build()
+                .unwrap(),
+        ];
+        let expected = [48, 16, 4, 3, 34];
+
+        for (filters, expected) in filters.iter().zip(expected) {
+            let compiled = filters[0].compile().unwrap();
+            let mut count = 0;
+            for record in &records {
+                let matches = compiled.matches(record);
+                assert_eq!(matches, filters[0].matches(record).unwrap());
+                if matches {
+                    count += 1;
+                }
+            }
+            assert_eq!(count, expected);
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use super::MatchContext;
 use crate::{Error, Id, InnerError, Kind, PublicKey, Record, Tag, Timestamp};
 use std::ops::{Deref, DerefMut};
 
@@ -212,7 +213,8 @@ impl FilterElement {
 
     /// Does this filter element match a given record?
     ///
-    /// Does not work with `ReceivedSince` or `ReceivedUntil`.
+    /// Does not work with `ReceivedSince` or `ReceivedUntil`; use
+    /// `matches_with_context()` for those.
     ///
     /// # Errors
     ///
@@ -241,13 +243,13 @@ impl FilterElement {
             FilterElementType::SIGNING_KEYS => {
                 let wordlen = self.0[1] as usize;
                 let len = wordlen * 8;
-                let pk = record.signing_public_key();
+                let signing_key = record.signing_key_bytes();
                 let mut i = 8;
                 loop {
                     if i + 32 > len {
                         return Ok(false);
                     }
-                    if &self.0[i..i + 32] == pk.as_bytes().as_slice() {
+                    if &self.0[i..i + 32] == signing_key.as_slice() {
                         return Ok(true);
                     }
                     i += 32;
@@ -309,16 +311,14 @@ impl FilterElement {
                 Err(InnerError::InvalidFilterElementForFunction.into())
             }
             FilterElementType::EXCLUDE => {
-                let wordlen = self.0[1] as usize;
-                for i in 1..wordlen {
-                    if record.id().as_bytes()[..32] == self.0[i * 8..i * 8 + 8] {
-                        return Ok(true);
-                    }
-                    if record.address().as_bytes()[..32] == self.0[i * 8..i * 8 + 8] {
-                        return Ok(true);
+                let id = record.id();
+                let address = record.address();
+                for prefix in self.0[8..].chunks_exact(32) {
+                    if id.as_bytes()[..32] == *prefix || address.as_bytes()[..32] == *prefix {
+                        return Ok(false);
                     }
                 }
-                Ok(false)
+                Ok(true)
             }
             FilterElementType::EXCLUDED_TAGS => {
                 let wordlen = self.0[1] as usize;
@@ -346,6 +346,34 @@ impl FilterElement {
         }
     }
 
+    /// Does this filter element match a given record, as received by a
+    /// server and requested by a client?
+    ///
+    /// Works with every type of filter element.
+    ///
+    /// # Errors
+    ///
+    /// Throws an error if Self is `Kinds` and the internal length is wrong.
+    /// Throws an error on any unknown `FilterElement`
+    #[allow(clippy::missing_panics_doc)]
+    pub fn matches_with_context(
+        &self,
+        record: &Record,
+        context: &MatchContext,
+    ) -> Result<bool, Error> {
+        match self.get_type() {
+            FilterElementType::RECEIVED_SINCE => {
+                let filter_ts = Timestamp::from_bytes(self.0[8..16].try_into().unwrap())?;
+                Ok(context.received_at >= filter_ts)
+            }
+            FilterElementType::RECEIVED_UNTIL => {
+                let filter_ts = Timestamp::from_bytes(self.0[8..16].try_into().unwrap())?;
+                Ok(context.received_at <= filter_ts)
+            }
+            _ => self.matches(record),
+        }
+    }
+
     /// Iterate over the keys
     #[must_use]
     pub fn keys(&self) -> Option<FeKeysIter<'_>> {
@@ -875,6 +903,36 @@ mod test {
         // TBD: This test could be far more complete
     }
 
+    #[test]
+    fn test_exclude_matches() {
+        let timestamp = Timestamp::from_unixtime(1_700_000_000, 0).unwrap();
+        let records: Vec<OwnedRecord> = (0..3)
+            .map(|_| {
+                let secret_key = SecretKey::generate();
+                let key = secret_key.public();
+                OwnedRecord::new(&RecordParts {
+                    signing_data: RecordSigningData::SecretKey(secret_key),
+                    address_data: RecordAddressData::Random(key, Kind::MICROBLOG_ROOT),
+                    timestamp,
+                    flags: RecordFlags::empty(),
+                    tag_set: &EMPTY_TAG_SET,
+                    payload: b"Hello World!",
+                })
+                .unwrap()
+            })
+            .collect();
+
+        // Exclude the first record by id and the second by address (only the
+        // first 32 bytes of each entry are stored)
+        let address = unsafe { Id::from_bytes_unchecked(records[1].address().as_bytes()) };
+        let fe = OwnedFilterElement::new_exclude(&[records[0].id(), address]).unwrap();
+        test_filter_element_type!(&fe, FilterElementType::EXCLUDE);
+
+        assert!(!fe.matches(&records[0]).unwrap());
+        assert!(!fe.matches(&records[1]).unwrap());
+        assert!(fe.matches(&records[2]).unwrap());
+    }
+
     #[test]
     #[allow(clippy::too_many_lines)]
     fn test_filter_element_iters() {
@@ -1,9 +1,29 @@
+mod builder;
+pub use builder::FilterBuilder;
+
+mod compiled;
+pub use compiled::CompiledFilter;
+
 mod filter_element;
 pub use filter_element::*;
 
-use crate::{Error, InnerError, Record};
+use crate::{Error, InnerError, PublicKey, Record, Timestamp};
 use std::ops::{Deref, DerefMut};
 
+/// What a server knows about a record, and about the client asking for it,
+/// beyond the record itself
+///
+/// Filter elements that depend on these are only evaluated by
+/// `Filter::matches_with_context()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MatchContext {
+    /// When the server received the record
+    pub received_at: Timestamp,
+
+    /// The authenticated key of the client asking, if any
+    pub requester: Option<PublicKey>,
+}
+
 /// A filter
 #[derive(Debug, PartialEq, Eq, Hash)]
 pub struct Filter([u8]);
@@ -108,6 +128,30 @@ impl Filter {
         Ok(true)
     }
 
+    /// Does this filter match a given record, as received by a server and
+    /// requested by a client?
+    ///
+    /// Unlike `matches()`, this evaluates every element, including
+    /// `ReceivedSince` and `ReceivedUntil`.
+    ///
+    /// # Errors
+    ///
+    /// Throws an error if an element is `Kinds` and the internal length is
+    /// wrong. Throws an error on any unknown `FilterElement`
+    pub fn matches_with_context(
+        &self,
+        record: &Record,
+        context: &MatchContext,
+    ) -> Result<bool, Error> {
+        for element in self.elements() {
+            if !element.matches_with_context(record, context)? {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
     /// Is the filter narrow?
     #[must_use]
     pub fn is_narrow(&self) -> bool {
@@ -277,4 +321,57 @@ mod test {
 
         assert_eq!(filter.elements().count(), 3);
     }
+
+    #[test]
+    fn test_filter_matches_with_context() {
+        let secret_key = SecretKey::generate();
+        let timestamp = Timestamp::from_unixtime(1_700_000_000, 0).unwrap();
+        let record = crate::RecordBuilder::new(Kind::EXAMPLE, secret_key.clone())
+            .timestamp(timestamp)
+            .sign()
+            .unwrap();
+        let later = timestamp + std::time::Duration::from_mins(1);
+
+        let filter = crate::FilterBuilder::new()
+            .kinds([Kind::EXAMPLE])
+            .received_since(later)
+            .received_until(later + std::time::Duration::from_mins(1))
+            .build()
+            .unwrap()
+            .remove(0);
+        let compiled = filter.compile().unwrap();
+
+        // Without a context the received-time bounds are skipped
+        assert!(filter.matches(&record).unwrap());
+        assert!(compiled.matches(&record));
+
+        for (received_at, expected) in [
+            (timestamp, false),
+            (later, true),
+            (later + std::time::Duration::from_secs(30), true),
+            (later + std::time::Duration::from_mins(2), false),
+        ] {
+            let context = MatchContext {
+                received_at,
+                requester: Some(secret_key.public()),
+            };
+            assert_eq!(
+                filter.matches_with_context(&record, &context).unwrap(),
+                expected
+            );
+            assert_eq!(compiled.matches_with_context(&record, &context), expected);
+        }
+
+        // Other elements still apply
+        let context = MatchContext {
+            received_at: later,
+            requester: None,
+        };
+        let filter = crate::FilterBuilder::new()
+            .kinds([Kind::PROFILE])
+            .build()
+            .unwrap()
+            .remove(0);
+        assert!(!filter.matches_with_context(&record, &context).unwrap());
+    }
 }
@@ -2,6 +2,9 @@ use digest::generic_array::typenum::U64;
 use digest::generic_array::GenericArray;
 use digest::{FixedOutput, HashMarker, OutputSizeUser, Reset, Update};
 
+#[cfg(feature = "rayon")]
+const RAYON_THRESHOLD: usize = 131_072;
+
 /// This is a Blake3 Hasher implementing the traits required for use in
 /// the ed25519 construction in place of SHA-512
 #[derive(Clone)]
@@ -19,12 +22,19 @@ impl Blake3 {
 
     pub(crate) fn hash(&mut self, data: &[u8], out: &mut [u8]) {
         let _ = self.h.reset();
-        let _ = self.h.update(data);
+        self.update(data);
         self.h.finalize_xof().fill(out);
     }
 
     /// Add data without finalizing
     pub(crate) fn update(&mut self, data: &[u8]) {
+        // Multithreading only pays off for large inputs
+        #[cfg(feature = "rayon")]
+        if data.len() >= RAYON_THRESHOLD {
+            let _ = self.h.update_rayon(data);
+            return;
+        }
+
         let _ = self.h.update(data);
     }
 
@@ -36,7 +46,7 @@ impl Blake3 {
 impl Update for Blake3 {
     #[inline]
     fn update(&mut self, data: &[u8]) {
-        let _ = self.h.update(data);
+        Blake3::update(self, data);
     }
 }
 
@@ -68,3 +78,35 @@ impl OutputSizeUser for Blake3 {
 }
 
 impl HashMarker for Blake3 {}
+
+/// A digest that has already been computed (by a `Blake3`), so that it can
+/// be signed with Ed25519ph without the data it was computed over.
+///
+/// Any further input is ignored.
+#[derive(Clone)]
+pub(crate) struct Prehashed(pub(crate) [u8; 64]);
+
+impl Default for Prehashed {
+    #[inline]
+    fn default() -> Self {
+        Prehashed([0; 64])
+    }
+}
+
+impl Update for Prehashed {
+    #[inline]
+    fn update(&mut self, _data: &[u8]) {}
+}
+
+impl FixedOutput for Prehashed {
+    #[inline]
+    fn finalize_into(self, out: &mut GenericArray<u8, Self::OutputSize>) {
+        out.copy_from_slice(&self.0);
+    }
+}
+
+impl OutputSizeUser for Prehashed {
+    type OutputSize = U64;
+}
+
+impl HashMarker for Prehashed {}
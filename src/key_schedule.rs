@@ -1,7 +1,9 @@
 use crate::{
     Error, InnerError, Kind, OwnedRecord, OwnedTag, OwnedTagSet, PublicKey, Record,
-    RecordAddressData, RecordFlags, RecordParts, RecordSigningData, SecretKey, Timestamp,
+    RecordAddressData, RecordFlags, RecordParts, RecordSigningData, SignatureScheme, Signer,
+    Timestamp,
 };
+use std::sync::Arc;
 
 /// Flags applying to a Subkey
 #[repr(u16)]
@@ -138,12 +140,67 @@ impl KeySchedule {
         self.0
     }
 
-    /// Create a new `OwnedRecord` based on this `KeySchedule`
+    /// Whether this `KeySchedule` authorizes the subkey with the given bytes to
+    /// sign, under the `SignatureScheme`, records with the `Timestamp`.
+    ///
+    /// An `ActiveSigningKey` may sign ed25519 records and an `ActiveNostrKey`
+    /// may sign secp256k1 records. A `RevokedAll` key may never sign. A
+    /// `RevokedPast` key may not sign records from before its timestamp, and an
+    /// `OutOfUse` key may not sign records from its timestamp onwards. These
+    /// markers only restrict a key, which must also be listed as active.
+    ///
+    /// This does not consider the master key, which is always authorized. See
+    /// `Record::verify_authorized()`.
+    #[must_use]
+    pub fn authorizes(
+        &self,
+        signing_key: &[u8; 32],
+        scheme: SignatureScheme,
+        timestamp: Timestamp,
+    ) -> bool {
+        let mut authorized = false;
+        for entry in self
+            .0
+            .iter()
+            .filter(|e| e.public_key.as_bytes() == signing_key)
+        {
+            match entry.marker {
+                SubkeyMarker::ActiveSigningKey => {
+                    authorized |= scheme == SignatureScheme::Ed25519;
+                }
+                SubkeyMarker::ActiveNostrKey => {
+                    authorized |= scheme == SignatureScheme::Secp256k1;
+                }
+                SubkeyMarker::RevokedAll => return false,
+                SubkeyMarker::RevokedPast => {
+                    if timestamp < entry.timestamp {
+                        return false;
+                    }
+                }
+                SubkeyMarker::OutOfUse => {
+                    if timestamp >= entry.timestamp {
+                        return false;
+                    }
+                }
+                SubkeyMarker::ActiveEncryptionKey | SubkeyMarker::Undefined(_) => {}
+            }
+        }
+        authorized
+    }
+
+    /// Create a new `OwnedRecord` based on this `KeySchedule`, signed by the
+    /// master key's `Signer` (such as its `SecretKey`)
     ///
     /// # Errors
     ///
-    /// This is unlikely to return an error.
-    pub fn as_record(&self, secret_key: SecretKey) -> Result<OwnedRecord, Error> {
+    /// Returns an error if the signer is not an ed25519 key, or if signing
+    /// fails.
+    pub fn as_record<S: Signer + 'static>(&self, signer: S) -> Result<OwnedRecord, Error> {
+        let scheme = signer.signature_scheme();
+        if scheme != SignatureScheme::Ed25519 {
+            return Err(InnerError::UnsupportedSignatureScheme(scheme).into());
+        }
+
         let mut payload = vec![0; 48 * self.0.len()];
         let mut tag_set = OwnedTagSet::new();
 
@@ -155,9 +212,9 @@ impl KeySchedule {
             payload.extend(kse.timestamp.to_bytes().as_slice());
         }
 
-        let public_key = secret_key.public();
+        let public_key = PublicKey::from_bytes(&signer.signing_key_bytes())?;
         let parts = RecordParts {
-            signing_data: RecordSigningData::SecretKey(secret_key),
+            signing_data: RecordSigningData::Signer(Arc::new(signer)),
             address_data: RecordAddressData::Random(public_key, Kind::KEY_SCHEDULE),
             timestamp: Timestamp::now()?,
             flags: RecordFlags::empty(),
@@ -224,6 +281,83 @@ impl KeySchedule {
 
 #[cfg(test)]
 mod test {
+    use crate::*;
+
+    #[test]
+    fn test_key_schedule_authorizes() {
+        let t = |secs: u64| Timestamp::from_unixtime(1_700_000_000 + secs, 0).unwrap();
+        let entry =
+            |key: &SecretKey, marker: SubkeyMarker, timestamp: Timestamp| KeyScheduleEntry {
+                public_key: key.public(),
+                marker,
+                timestamp,
+            };
+
+        let active = SecretKey::generate();
+        let revoked_all = SecretKey::generate();
+        let revoked_past = SecretKey::generate();
+        let out_of_use = SecretKey::generate();
+        let inactive = SecretKey::generate();
+        let encryption = SecretKey::generate();
+        let unlisted = SecretKey::generate();
+        let key_schedule = KeySchedule::new(vec![
+            entry(&active, SubkeyMarker::ActiveSigningKey, Timestamp::ZERO),
+            entry(
+                &revoked_all,
+                SubkeyMarker::ActiveSigningKey,
+                Timestamp::ZERO,
+            ),
+            entry(&revoked_all, SubkeyMarker::RevokedAll, t(100)),
+            entry(
+                &revoked_past,
+                SubkeyMarker::ActiveSigningKey,
+                Timestamp::ZERO,
+            ),
+            entry(&revoked_past, SubkeyMarker::RevokedPast, t(100)),
+            entry(&out_of_use, SubkeyMarker::ActiveSigningKey, Timestamp::ZERO),
+            entry(&out_of_use, SubkeyMarker::OutOfUse, t(100)),
+            entry(&inactive, SubkeyMarker::RevokedPast, t(100)),
+            entry(&inactive, SubkeyMarker::OutOfUse, t(300)),
+            entry(
+                &encryption,
+                SubkeyMarker::ActiveEncryptionKey,
+                Timestamp::ZERO,
+            ),
+        ])
+        .unwrap();
+
+        let authorizes = |key: &SecretKey, timestamp: Timestamp| {
+            key_schedule.authorizes(key.public().as_bytes(), SignatureScheme::Ed25519, timestamp)
+        };
+        for timestamp in [t(0), t(200)] {
+            assert!(authorizes(&active, timestamp));
+            assert!(!authorizes(&revoked_all, timestamp));
+            assert!(!authorizes(&encryption, timestamp));
+            assert!(!authorizes(&unlisted, timestamp));
+
+            // Restricting markers alone don't authorize a key
+            assert!(!authorizes(&inactive, timestamp));
+        }
+        assert!(!authorizes(&revoked_past, t(99)));
+        assert!(authorizes(&revoked_past, t(100)));
+        assert!(authorizes(&out_of_use, t(99)));
+        assert!(!authorizes(&out_of_use, t(100)));
+
+        // An ed25519 signing key can't sign under secp256k1
+        assert!(!key_schedule.authorizes(
+            active.public().as_bytes(),
+            SignatureScheme::Secp256k1,
+            t(0)
+        ));
+
+        // Only an ed25519 master key can publish the key schedule
+        let nostr_key = SchnorrSigningKey::random(&mut scrypt::password_hash::rand_core::OsRng);
+        assert!(matches!(
+            key_schedule.as_record(nostr_key).unwrap_err().inner,
+            InnerError::UnsupportedSignatureScheme(SignatureScheme::Secp256k1)
+        ));
+    }
+
     #[test]
     #[ignore = "temporarily skipped pending KeySchedule test implementation"]
     fn test_key_schedule() {
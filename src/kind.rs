@@ -32,15 +32,9 @@ impl Kind {
 
 impl std::fmt::Display for Kind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match *self {
-            Kind::EXAMPLE => write!(f, "Example"),
-            Kind::KEY_SCHEDULE => write!(f, "Key Schedule"),
-            Kind::PROFILE => write!(f, "Profile"),
-            Kind::MICROBLOG_ROOT => write!(f, "Microblog Root"),
-            Kind::REPLY_COMMENT => write!(f, "Reply Comment"),
-            Kind::BLOG_POST => write!(f, "Blog Post"),
-            Kind::CHAT_MESSAGE => write!(f, "Chat Message"),
-            _other => write!(f, "Kind({:x?})", self.0),
+        match self.name() {
+            Some(name) => write!(f, "{name}"),
+            None => write!(f, "Kind({:x?})", self.0),
         }
     }
 }
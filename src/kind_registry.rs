@@ -0,0 +1,309 @@
+use crate::{Error, InnerError, Kind, Profile, Record};
+use std::collections::BTreeMap;
+use std::sync::{PoisonError, RwLock};
+
+type ValidateFn = fn(&[u8]) -> Result<(), Error>;
+type FormatFn = fn(&[u8], &mut std::fmt::Formatter<'_>) -> std::fmt::Result;
+
+/// How a `Kind` is named, described, and how its payload is validated and
+/// displayed.
+///
+/// Register it with `Kind::register()` or `Kind::register_application_kind()`.
+/// Records of the kind are then shown by name wherever kinds are displayed
+/// (including JSON export), and their payloads are checked by the
+/// `PayloadMatchesKind` validation rule.
+#[derive(Debug, Clone, Copy)]
+pub struct KindRegistration {
+    /// The name of the kind
+    pub name: &'static str,
+
+    /// What records of the kind are for
+    pub description: &'static str,
+
+    /// Checks the (decompressed) payload
+    pub validate: Option<ValidateFn>,
+
+    /// Writes the (decompressed) payload for display. It is only given
+    /// payloads that `validate` accepted.
+    pub format: Option<FormatFn>,
+}
+
+// Application id, application-specific kind, and the kind flags (or `None`
+// for registrations that cover every combination of flags)
+type Key = (u64, u16, Option<u16>);
+
+static REGISTRY: RwLock<BTreeMap<Key, KindRegistration>> = RwLock::new(BTreeMap::new());
+
+impl Kind {
+    /// Register an application-defined kind, for the whole process until it
+    /// is unregistered.
+    ///
+    /// ```
+    /// # use mosaic_core::*;
+    /// let kind = Kind::from_parts(0x77, 1, KindFlags::from_bits_retain(0x1c));
+    /// kind.register(KindRegistration {
+    ///     name: "Recipe",
+    ///     description: "A recipe, as Markdown",
+    ///     validate: None,
+    ///     format: None,
+    /// })
+    /// .unwrap();
+    ///
+    /// assert_eq!(kind.to_string(), "Recipe");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the kind is one defined by this crate, or is
+    /// already registered.
+    pub fn register(self, registration: KindRegistration) -> Result<(), Error> {
+        self.insert(Some(self.flags().bits()), registration)
+    }
+
+    /// Register an application-defined kind whatever its flags, for the whole
+    /// process until it is unregistered. A registration with `register()` for
+    /// the exact `Kind` takes precedence.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if a kind defined by this crate has the same
+    /// application id and application-specific kind, or it is already
+    /// registered.
+    pub fn register_application_kind(
+        application_id: u64,
+        application_kind: u16,
+        registration: KindRegistration,
+    ) -> Result<(), Error> {
+        Kind::from_parts(application_id, application_kind, crate::KindFlags::empty())
+            .insert(None, registration)
+    }
+
+    fn insert(self, flags: Option<u16>, registration: KindRegistration) -> Result<(), Error> {
+        let built_in = match flags {
+            Some(_) => self.is_built_in(),
+            None => BUILT_IN.iter().any(|kind| {
+                kind.application_id() == self.application_id()
+                    && kind.application_specific_kind() == self.application_specific_kind()
+            }),
+        };
+        if built_in {
+            return Err(InnerError::KindInUse(self).into());
+        }
+        let mut registry = REGISTRY.write().unwrap_or_else(PoisonError::into_inner);
+        let key = (
+            self.application_id(),
+            self.application_specific_kind(),
+            flags,
+        );
+        if registry.contains_key(&key) {
+            return Err(InnerError::KindInUse(self).into());
+        }
+        let _ = registry.insert(key, registration);
+        Ok(())
+    }
+
+    /// Remove a registration made with `register()` for this exact kind,
+    /// returning it if there was one
+    #[must_use]
+    pub fn unregister(self) -> Option<KindRegistration> {
+        self.remove(Some(self.flags().bits()))
+    }
+
+    /// Remove a registration made with `register_application_kind()`,
+    /// returning it if there was one
+    #[must_use]
+    pub fn unregister_application_kind(
+        application_id: u64,
+        application_kind: u16,
+    ) -> Option<KindRegistration> {
+        Kind::from_parts(application_id, application_kind, crate::KindFlags::empty()).remove(None)
+    }
+
+    fn remove(self, flags: Option<u16>) -> Option<KindRegistration> {
+        REGISTRY
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&(
+                self.application_id(),
+                self.application_specific_kind(),
+                flags,
+            ))
+    }
+
+    /// The registration of the kind: built in, registered for this exact
+    /// kind, or registered for its application kind
+    #[must_use]
+    pub fn registration(&self) -> Option<KindRegistration> {
+        if let Some(registration) = self.built_in_registration() {
+            return Some(registration);
+        }
+        let registry = REGISTRY.read().unwrap_or_else(PoisonError::into_inner);
+        let (app_id, app_kind) = (self.application_id(), self.application_specific_kind());
+        registry
+            .get(&(app_id, app_kind, Some(self.flags().bits())))
+            .or_else(|| registry.get(&(app_id, app_kind, None)))
+            .copied()
+    }
+
+    /// The name of the kind, if it is defined by this crate or registered
+    #[must_use]
+    pub fn name(&self) -> Option<&'static str> {
+        self.registration().map(|r| r.name)
+    }
+
+    /// The description of the kind, if it is defined by this crate or
+    /// registered
+    #[must_use]
+    pub fn description(&self) -> Option<&'static str> {
+        self.registration().map(|r| r.description)
+    }
+
+    /// Is this a kind defined by this crate?
+    #[must_use]
+    pub fn is_built_in(&self) -> bool {
+        BUILT_IN.contains(self)
+    }
+
+    fn built_in_registration(self) -> Option<KindRegistration> {
+        let (name, description, validate): (_, _, ValidateFn) = match self {
+            Kind::EXAMPLE => ("Example", "An example for documentation and tests", utf8),
+            Kind::KEY_SCHEDULE => (
+                "Key Schedule",
+                "The subkeys a user has authorized or revoked",
+                key_schedule,
+            ),
+            Kind::PROFILE => ("Profile", "A user's profile", profile),
+            Kind::MICROBLOG_ROOT => ("Microblog Root", "A short post starting a thread", utf8),
+            Kind::REPLY_COMMENT => ("Reply Comment", "A reply to another record", utf8),
+            Kind::BLOG_POST => ("Blog Post", "A long-form article, in Markdown", utf8),
+            Kind::CHAT_MESSAGE => ("Chat Message", "A message in a chat", utf8),
+            _ => return None,
+        };
+        Some(KindRegistration {
+            name,
+            description,
+            validate: Some(validate),
+            format: None,
+        })
+    }
+}
+
+const BUILT_IN: [Kind; 7] = [
+    Kind::EXAMPLE,
+    Kind::KEY_SCHEDULE,
+    Kind::PROFILE,
+    Kind::MICROBLOG_ROOT,
+    Kind::REPLY_COMMENT,
+    Kind::BLOG_POST,
+    Kind::CHAT_MESSAGE,
+];
+
+fn utf8(payload: &[u8]) -> Result<(), Error> {
+    let _ = std::str::from_utf8(payload)?;
+    Ok(())
+}
+
+fn key_schedule(payload: &[u8]) -> Result<(), Error> {
+    if payload.len().is_multiple_of(48) {
+        Ok(())
+    } else {
+        Err("Invalid KeySchedule Record payload len".into())
+    }
+}
+
+fn profile(payload: &[u8]) -> Result<(), Error> {
+    let _ = Profile::from_cbor_bytes(payload)?;
+    Ok(())
+}
+
+// The payload of the record as written by the registered formatter of its
+// kind, if it has one and the payload is valid
+pub(crate) fn format_payload(record: &Record) -> Option<String> {
+    struct Formatted<'a>(FormatFn, &'a [u8]);
+
+    impl std::fmt::Display for Formatted<'_> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            (self.0)(self.1, f)
+        }
+    }
+
+    let registration = record.kind().registration()?;
+    let format = registration.format?;
+    let payload = record.payload().ok()?;
+    if let Some(validate) = registration.validate {
+        validate(&payload).ok()?;
+    }
+    Some(Formatted(format, &payload).to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn test_kind_registry() {
+        let registration = KindRegistration {
+            name: "Score",
+            description: "A game score",
+            validate: Some(|payload| {
+                if payload.len() == 4 {
+                    Ok(())
+                } else {
+                    Err(InnerError::WrongLength.into())
+                }
+            }),
+            format: Some(|payload, f| {
+                write!(
+                    f,
+                    "{} points",
+                    u32::from_le_bytes(payload.try_into().unwrap())
+                )
+            }),
+        };
+
+        // Built-in kinds can't be registered, nor can kinds be registered twice
+        assert!(Kind::PROFILE.register(registration).is_err());
+        assert!(Kind::register_application_kind(0, 2, registration).is_err());
+        let flags = KindFlags::from_bits_retain(0x1c);
+        let score = Kind::from_parts(0x9001, 1, flags);
+        assert!(score.registration().is_none());
+        assert_eq!(score.to_string(), "Kind(90010001001c)");
+        Kind::register_application_kind(0x9001, 1, registration).unwrap();
+        assert!(Kind::register_application_kind(0x9001, 1, registration).is_err());
+
+        // The application kind registration covers every set of flags, but
+        // is overridden by an exact registration
+        assert_eq!(score.name(), Some("Score"));
+        assert_eq!(score.description(), Some("A game score"));
+        let other_flags = Kind::from_parts(0x9001, 1, KindFlags::empty());
+        assert_eq!(other_flags.to_string(), "Score");
+        other_flags
+            .register(KindRegistration {
+                name: "Raw Score",
+                ..registration
+            })
+            .unwrap();
+        assert_eq!(other_flags.to_string(), "Raw Score");
+        assert_eq!(score.to_string(), "Score");
+
+        assert_eq!(Kind::BLOG_POST.name(), Some("Blog Post"));
+        assert!(Kind::BLOG_POST.is_built_in());
+        assert!(!score.is_built_in());
+
+        let record = RecordBuilder::new(score, SecretKey::generate())
+            .timestamp(Timestamp::from_unixtime(1_700_000_000, 0).unwrap())
+            .payload(&42_u32.to_le_bytes())
+            .unwrap()
+            .sign()
+            .unwrap();
+        assert!(record.to_string().contains("  kind: Score\n"));
+        assert!(record.to_string().contains("  payload: 42 points\n"));
+
+        assert_eq!(other_flags.unregister().map(|r| r.name), Some("Raw Score"));
+        assert_eq!(other_flags.to_string(), "Score");
+        assert!(score.unregister().is_none());
+        assert!(Kind::unregister_application_kind(0x9001, 1).is_some());
+        assert!(score.registration().is_none());
+        assert_eq!(score.to_string(), "Kind(90010001001c)");
+    }
+}
@@ -8,6 +8,13 @@
 //! into their [`DalekVerifyingKey`] or [`DalekSigningKey`] respectively in
 //! order to do cryptographic operations.
 //!
+//! [`Record`]s may also be signed with a secp256k1 [`SchnorrSigningKey`]
+//! (such as a nostr key) under [`SignatureScheme::Secp256k1`]. Either kind of
+//! signature is a [`RecordSignature`].
+//!
+//! Keys may be held elsewhere (a key agent or another process) behind a
+//! [`Signer`] or [`AsyncSigner`].
+//!
 //! # Bootstrap
 //!
 //! Server endpoints (URLs) are bootstrapped from Mainline DHT with
@@ -19,19 +26,50 @@
 //! # Records
 //!
 //! [`Record`]s are of various [`Kind`]s and have [`Timestamp`]s and
-//! [`RecordFlags`].
+//! [`RecordFlags`]. Applications may register their own kinds with a
+//! [`KindRegistration`].
+//!
+//! [`Record`]s may have [`Tag`]s of varying [`TagType`]s, which can be
+//! parsed into a [`ParsedTag`]. Applications may register their own tag
+//! types with a [`TagTypeRegistration`]. Printable content with content segment tags
+//! can be split into [`ContentSpan`]s for display with [`RichText`], and
+//! composed with a [`ContentComposer`].
+//!
+//! Records of the built-in content kinds can be made and read as a
+//! [`Profile`], [`MicroblogRoot`], [`ReplyComment`], [`ChatMessage`] or
+//! [`BlogPost`].
+//!
+//! New [`Record`]s are most easily assembled and signed with a
+//! [`RecordBuilder`]. To sign elsewhere, prepare an [`UnsignedRecord`] and
+//! finalize it with the signature of its digest.
+//!
+//! Sequences of [`Record`]s can be read from and written to streams with a
+//! [`RecordReader`] and [`RecordWriter`] (or their async equivalents).
 //!
-//! [`Record`]s may have [`Tag`]s of varying [`TagType`]s.
+//! Collections of [`Record`]s can be stored in an indexed [`Archive`],
+//! assembled with an [`ArchiveWriter`].
 //!
 //! Every [`Record`] has an [`Id`] and an [`Address`] by which it can be
 //! referred. In some contexts a [`Record`] may be referred to by either,
 //! and so a [`Reference`] type can be used when it is unknown which kind
 //! of reference is specified.
 //!
+//! Conversations can be reconstructed from replies into a [`Thread`].
+//!
+//! Nostr events can be converted to and from [`Record`]s with a
+//! `NostrBridge` (with the `nostr` feature).
+//!
+//! Beyond being valid, [`Record`]s can be checked against policy (such as
+//! payload size limits) with a [`RecordValidator`].
+//!
 //! # Protocol
 //!
 //! Protocol [`Message`]s are sent between client and server over some
-//! transport. Many client-initiated messages include a [`Filter`]
+//! transport. Many client-initiated messages include a [`Filter`], which a
+//! [`FilterBuilder`] can build, split across several if the lists are long.
+//! Servers matching many records against a filter can first
+//! [`Filter::compile`] it into a [`CompiledFilter`]. To evaluate the
+//! received-time elements too, match with a [`MatchContext`].
 
 #![warn(clippy::pedantic)]
 #![deny(
@@ -63,29 +101,50 @@ macro_rules! padded_len {
 pub use ed25519_dalek::Signature as DalekSignature;
 pub use ed25519_dalek::SigningKey as DalekSigningKey;
 pub use ed25519_dalek::VerifyingKey as DalekVerifyingKey;
+pub use k256::schnorr::Signature as SchnorrSignature;
+pub use k256::schnorr::SigningKey as SchnorrSigningKey;
+pub use k256::schnorr::VerifyingKey as SchnorrVerifyingKey;
 pub use mainline;
 pub use rand;
 
 mod address;
 pub use address::Address;
 
+mod archive;
+#[cfg(feature = "mmap")]
+pub use archive::MappedArchive;
+pub use archive::{Archive, ArchiveIter, ArchiveWriter};
+
+mod blog_post;
+pub use blog_post::{BlogPost, CoverImage};
+
+mod chat;
+pub use chat::ChatMessage;
+
+mod content;
+pub use content::{ContentComposer, ContentSpan, RichText};
+
 mod error;
 pub use error::{Error, InnerError};
 
 mod filter;
 pub use filter::{
-    FeIdPrefixesIter, FeKeysIter, FeKindsIter, FeTagsIter, FeTimestampsIter, Filter, FilterElement,
-    FilterElementType, OwnedFilter, OwnedFilterElement,
+    CompiledFilter, FeIdPrefixesIter, FeKeysIter, FeKindsIter, FeTagsIter, FeTimestampsIter,
+    Filter, FilterBuilder, FilterElement, FilterElementType, MatchContext, OwnedFilter,
+    OwnedFilterElement,
 };
 
 mod hash;
-pub(crate) use hash::Blake3;
+pub(crate) use hash::{Blake3, Prehashed};
 
 mod id;
 pub use id::Id;
 
 mod signature;
-pub use signature::{EncryptedSecretKey, PublicKey, SecretKey};
+pub use signature::{EncryptedSecretKey, PublicKey, RecordSignature, SecretKey};
+
+mod signer;
+pub use signer::{AsyncSigner, MockSigner, Signer, SignerIdentity, SigningRequest};
 
 mod key_schedule;
 pub use key_schedule::{KeySchedule, KeyScheduleEntry, SubkeyMarker};
@@ -93,9 +152,20 @@ pub use key_schedule::{KeySchedule, KeyScheduleEntry, SubkeyMarker};
 mod kind;
 pub use kind::Kind;
 
+mod kind_registry;
+pub use kind_registry::KindRegistration;
+
 mod kind_flags;
 pub use kind_flags::{DuplicateHandling, KindFlags, ReadAccess};
 
+#[cfg(feature = "nostr")]
+mod nostr;
+#[cfg(feature = "nostr")]
+pub use nostr::{NostrBridge, NostrEvent, NostrImport};
+
+mod microblog;
+pub use microblog::{MicroblogRoot, ReplyComment};
+
 mod protocol;
 pub use protocol::{Message, MessageType, QueryId, ResultCode};
 
@@ -104,8 +174,9 @@ pub use profile::Profile;
 
 mod record;
 pub use record::{
-    OwnedRecord, Record, RecordAddressData, RecordFlags, RecordParts, RecordSigningData,
-    SignatureScheme,
+    AsyncRecordReader, AsyncRecordWriter, OwnedRecord, Record, RecordAddressData, RecordBuilder,
+    RecordFlags, RecordParts, RecordReader, RecordSigningData, RecordWriter, SignatureScheme,
+    UnsignedRecord, MAX_DECOMPRESSED_PAYLOAD_LEN,
 };
 
 mod reference;
@@ -115,10 +186,16 @@ mod server_bootstrap;
 pub use server_bootstrap::ServerBootstrap;
 
 mod tag;
-pub use tag::{OwnedTag, Tag, TagType};
+pub use tag::{OwnedTag, ParsedTag, Tag, TagType};
+
+mod tag_registry;
+pub use tag_registry::TagTypeRegistration;
 
 mod tag_set;
-pub use tag_set::{OwnedTagSet, TagSet, TagSetIter, EMPTY_TAG_SET};
+pub use tag_set::{OwnedTagSet, TagSet, TagSetIter, TagSetTryIter, EMPTY_TAG_SET, MAX_TAG_SET_LEN};
+
+mod thread;
+pub use thread::{Thread, ThreadNode};
 
 mod timestamp;
 pub use timestamp::{Timestamp, MAX_NANOSECONDS};
@@ -128,3 +205,9 @@ pub use url::Url;
 
 mod user_bootstrap;
 pub use user_bootstrap::{ServerUsage, UserBootstrap};
+
+mod validator;
+pub use validator::{
+    KnownTagTypes, MaxFutureTimestamp, MaxPayloadLen, PayloadMatchesKind, PrintableContentIsUtf8,
+    RecordValidator, RequireFromAuthor, ValidationRule, Violation,
+};
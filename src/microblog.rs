@@ -0,0 +1,299 @@
+use crate::{
+    Error, InnerError, Kind, OwnedRecord, OwnedTag, OwnedTagSet, ParsedTag, PublicKey, Record,
+    RecordBuilder, Reference, Signer, TagType,
+};
+use std::sync::Arc;
+
+/// A microblog post that starts a thread (a `MICROBLOG_ROOT` record)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MicroblogRoot {
+    /// The text of the post
+    pub content: String,
+
+    /// Any other tags, such as mentions and hashtags
+    pub tags: OwnedTagSet,
+}
+
+impl MicroblogRoot {
+    /// Create a new post
+    #[must_use]
+    pub fn new(content: &str) -> MicroblogRoot {
+        MicroblogRoot {
+            content: content.to_owned(),
+            tags: OwnedTagSet::new(),
+        }
+    }
+
+    /// A `RecordBuilder` for a record of this post, to be signed by the
+    /// author's `Signer` (such as their `SecretKey`, or a subkey's). Set the
+    /// timestamp or flags on it before signing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the post is too large to fit in a record.
+    pub fn record_builder<S: Signer + 'static>(
+        &self,
+        author: PublicKey,
+        signer: S,
+    ) -> Result<RecordBuilder, Error> {
+        builder(
+            Kind::MICROBLOG_ROOT,
+            author,
+            signer,
+            &[],
+            &self.tags,
+            &self.content,
+        )
+    }
+
+    /// Create a new `OwnedRecord` of this post, signed by the author's
+    /// `Signer` (such as their `SecretKey`, or a subkey's)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the post is too large to fit in a record, or if
+    /// signing fails.
+    pub fn as_record<S: Signer + 'static>(
+        &self,
+        author: PublicKey,
+        signer: S,
+    ) -> Result<OwnedRecord, Error> {
+        self.record_builder(author, signer)?.sign()
+    }
+
+    /// Extract a `MicroblogRoot` from a `MICROBLOG_ROOT` `Record`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Record is the wrong kind, or doesn't validate,
+    /// or its content is not UTF-8.
+    pub fn from_record(record: &Record) -> Result<MicroblogRoot, Error> {
+        Ok(MicroblogRoot {
+            content: content(record, Kind::MICROBLOG_ROOT)?,
+            tags: record.tag_set().to_owned(),
+        })
+    }
+}
+
+/// A reply to another record (a `REPLY_COMMENT` record)
+///
+/// It carries a `REPLY` tag for the record it replies to, and a `ROOT` tag for
+/// the record that started the thread.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplyComment {
+    /// The record replied to, and its kind
+    pub parent: (Reference, Kind),
+
+    /// The record that started the thread, and its kind
+    pub root: (Reference, Kind),
+
+    /// The text of the reply
+    pub content: String,
+
+    /// Any other tags, such as mentions and hashtags
+    pub tags: OwnedTagSet,
+}
+
+impl ReplyComment {
+    /// Create a reply to the `parent` record. The root is the parent's root,
+    /// or the parent itself if it has none.
+    #[must_use]
+    pub fn new(parent: &Record, content: &str) -> ReplyComment {
+        let parent_reference = (parent.id().to_reference(), parent.kind());
+        let root = parent
+            .tag_set()
+            .iter()
+            .find_map(|tag| match tag.parse() {
+                Ok(ParsedTag::Root { reference, kind }) => Some((reference, kind)),
+                _ => None,
+            })
+            .unwrap_or(parent_reference);
+        ReplyComment {
+            parent: parent_reference,
+            root,
+            content: content.to_owned(),
+            tags: OwnedTagSet::new(),
+        }
+    }
+
+    /// A `RecordBuilder` for a record of this reply, to be signed by the
+    /// author's `Signer` (such as their `SecretKey`, or a subkey's). Set the
+    /// timestamp or flags on it before signing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reply is too large to fit in a record.
+    pub fn record_builder<S: Signer + 'static>(
+        &self,
+        author: PublicKey,
+        signer: S,
+    ) -> Result<RecordBuilder, Error> {
+        let threading = [
+            OwnedTag::new_reply(&self.parent.0, self.parent.1),
+            OwnedTag::new_root(&self.root.0, self.root.1),
+        ];
+        builder(
+            Kind::REPLY_COMMENT,
+            author,
+            signer,
+            &threading,
+            &self.tags,
+            &self.content,
+        )
+    }
+
+    /// Create a new `OwnedRecord` of this reply, signed by the author's
+    /// `Signer` (such as their `SecretKey`, or a subkey's)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reply is too large to fit in a record, or if
+    /// signing fails.
+    pub fn as_record<S: Signer + 'static>(
+        &self,
+        author: PublicKey,
+        signer: S,
+    ) -> Result<OwnedRecord, Error> {
+        self.record_builder(author, signer)?.sign()
+    }
+
+    /// Extract a `ReplyComment` from a `REPLY_COMMENT` `Record`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Record is the wrong kind, or doesn't validate,
+    /// or its content is not UTF-8, or it lacks a `REPLY` or `ROOT` tag.
+    pub fn from_record(record: &Record) -> Result<ReplyComment, Error> {
+        let content = content(record, Kind::REPLY_COMMENT)?;
+        let (reply, root, tags) = threading_tags(record);
+        Ok(ReplyComment {
+            parent: reply.ok_or(InnerError::MissingTag(TagType::REPLY).into_err())?,
+            root: root.ok_or(InnerError::MissingTag(TagType::ROOT).into_err())?,
+            content,
+            tags,
+        })
+    }
+}
+
+// A builder for a printable record by the author with the tags and content
+pub(crate) fn builder<S: Signer + 'static>(
+    kind: Kind,
+    author: PublicKey,
+    signer: S,
+    first_tags: &[OwnedTag],
+    tags: &OwnedTagSet,
+    content: &str,
+) -> Result<RecordBuilder, Error> {
+    let mut builder = RecordBuilder::new_with_signer(kind, author, Arc::new(signer));
+    for tag in first_tags {
+        builder = builder.tag(tag)?;
+    }
+    for tag in tags.iter() {
+        builder = builder.tag(tag)?;
+    }
+    builder.payload(content.as_bytes())
+}
+
+// The content of a verified record of the kind
+pub(crate) fn content(record: &Record, kind: Kind) -> Result<String, Error> {
+    record.verify()?;
+
+    if record.kind() != kind {
+        return Err(InnerError::WrongKind.into());
+    }
+
+    Ok(std::str::from_utf8(&record.payload()?)?.to_owned())
+}
+
+// A referenced record and its kind, as in `REPLY` and `ROOT` tags
+type Target = (Reference, Kind);
+
+// The first REPLY and ROOT tags, and the rest of the tags
+pub(crate) fn threading_tags(record: &Record) -> (Option<Target>, Option<Target>, OwnedTagSet) {
+    let mut reply = None;
+    let mut root = None;
+    let mut rest = OwnedTagSet::new();
+    for tag in record.tag_set() {
+        match tag.parse() {
+            Ok(ParsedTag::Reply { reference, kind }) if reply.is_none() => {
+                reply = Some((reference, kind));
+            }
+            Ok(ParsedTag::Root { reference, kind }) if root.is_none() => {
+                root = Some((reference, kind));
+            }
+            _ => rest.add_tag(tag),
+        }
+    }
+    (reply, root, rest)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn test_microblog() {
+        let secret_key = SecretKey::generate();
+        let timestamp = Timestamp::from_unixtime(1_700_000_000, 0).unwrap();
+
+        let mut post = MicroblogRoot::new("Hello World!");
+        post.tags.add_tag(&OwnedTag::new_hashtag("hello").unwrap());
+        let root = post
+            .record_builder(secret_key.public(), secret_key.clone())
+            .unwrap()
+            .timestamp(timestamp)
+            .sign()
+            .unwrap();
+        assert_eq!(root.kind(), Kind::MICROBLOG_ROOT);
+        assert_eq!(MicroblogRoot::from_record(&root).unwrap(), post);
+
+        // A reply to the root has the root as both parent and root
+        let reply = ReplyComment::new(&root, "Hi!");
+        let root_reference = (root.id().to_reference(), Kind::MICROBLOG_ROOT);
+        assert_eq!(reply.parent, root_reference);
+        assert_eq!(reply.root, root_reference);
+        let reply_record = reply
+            .record_builder(secret_key.public(), secret_key.clone())
+            .unwrap()
+            .timestamp(timestamp)
+            .sign()
+            .unwrap();
+        assert_eq!(ReplyComment::from_record(&reply_record).unwrap(), reply);
+
+        // A reply to the reply keeps the root
+        let reply2 = ReplyComment::new(&reply_record, "Hi again");
+        assert_eq!(
+            reply2.parent,
+            (reply_record.id().to_reference(), Kind::REPLY_COMMENT)
+        );
+        assert_eq!(reply2.root, root_reference);
+
+        // A subkey signs for the author
+        let subkey = SecretKey::generate();
+        let record = post
+            .record_builder(secret_key.public(), subkey.clone())
+            .unwrap()
+            .timestamp(timestamp)
+            .sign()
+            .unwrap();
+        assert_eq!(record.author_public_key(), secret_key.public());
+        assert_eq!(record.signing_public_key(), Some(subkey.public()));
+
+        // Kinds are checked
+        let e = ReplyComment::from_record(&root).unwrap_err();
+        assert!(matches!(e.inner, InnerError::WrongKind));
+        let e = MicroblogRoot::from_record(&reply_record).unwrap_err();
+        assert!(matches!(e.inner, InnerError::WrongKind));
+
+        // Replies must have threading tags
+        let bare = RecordBuilder::new(Kind::REPLY_COMMENT, secret_key)
+            .timestamp(timestamp)
+            .tag(&OwnedTag::new_reply(&root_reference.0, root_reference.1))
+            .unwrap()
+            .sign()
+            .unwrap();
+        let e = ReplyComment::from_record(&bare).unwrap_err();
+        assert!(matches!(e.inner, InnerError::MissingTag(TagType::ROOT)));
+    }
+}
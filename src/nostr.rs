@@ -0,0 +1,750 @@
+use crate::{
+    BlogPost, CoverImage, Error, InnerError, Kind, OwnedTag, OwnedTagSet, ParsedTag, Profile,
+    PublicKey, Record, RecordAddressData, RecordFlags, RecordParts, RecordSigningData, Reference,
+    SchnorrSignature, SchnorrSigningKey, SchnorrVerifyingKey, SignatureScheme, Timestamp,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+/// A Nostr event, as defined by NIP-01
+///
+/// Ids, public keys and signatures are in lowercase hex. An event that has
+/// not been signed has no `sig`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NostrEvent {
+    /// The event id: the SHA-256 of the serialized event
+    pub id: String,
+
+    /// The author's x-only secp256k1 public key
+    pub pubkey: String,
+
+    /// Unix time in seconds
+    pub created_at: u64,
+
+    /// The event kind
+    pub kind: u32,
+
+    /// The tags
+    pub tags: Vec<Vec<String>>,
+
+    /// The content
+    pub content: String,
+
+    /// The BIP-340 signature of the id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sig: Option<String>,
+}
+
+impl NostrEvent {
+    /// Parse from JSON
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the input is not a JSON Nostr event
+    pub fn from_json(json: &str) -> Result<NostrEvent, Error> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Export as JSON
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Compute the id from the other fields
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn compute_id(&self) -> [u8; 32] {
+        let serialized = serde_json::to_string(&(
+            0,
+            &self.pubkey,
+            self.created_at,
+            self.kind,
+            &self.tags,
+            &self.content,
+        ))
+        .unwrap();
+        Sha256::digest(serialized.as_bytes()).into()
+    }
+
+    /// Set the `pubkey`, `id` and `sig` by signing with the key
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if signing fails
+    pub fn sign(&mut self, signing_key: &SchnorrSigningKey) -> Result<(), Error> {
+        self.pubkey = to_hex(&signing_key.verifying_key().to_bytes());
+        let id = self.compute_id();
+        let mut aux_rand = [0_u8; 32];
+        rand::RngCore::fill_bytes(&mut rand::rng(), &mut aux_rand);
+        let sig = signing_key
+            .sign_raw(&id, &aux_rand)
+            .map_err(|e| InnerError::Secp256k1(e).into_err())?;
+        self.id = to_hex(&id);
+        self.sig = Some(to_hex(&sig.to_bytes()));
+        Ok(())
+    }
+
+    /// Verify that the id matches the event and that it is signed by the
+    /// `pubkey`
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the event is unsigned, or the id or signature is
+    /// not valid
+    pub fn verify(&self) -> Result<(), Error> {
+        let id = self.compute_id();
+        if from_hex::<32>(&self.id)? != id {
+            return Err(InnerError::InvalidNostrEvent.into());
+        }
+        let Some(sig) = &self.sig else {
+            return Err(InnerError::MissingSignature.into());
+        };
+        let verifying_key = SchnorrVerifyingKey::from_bytes(&from_hex::<32>(&self.pubkey)?)
+            .map_err(|e| InnerError::Secp256k1(e).into_err())?;
+        let sig = SchnorrSignature::try_from(from_hex::<64>(sig)?.as_slice())
+            .map_err(|e| InnerError::Secp256k1(e).into_err())?;
+        verifying_key
+            .verify_raw(&id, &sig)
+            .map_err(|e| InnerError::Secp256k1(e).into_err())
+    }
+
+    // The value of the first tag with the name
+    fn tag_value(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|tag| tag.first().map(String::as_str) == Some(name))
+            .and_then(|tag| tag.get(1))
+            .map(String::as_str)
+    }
+
+    // The values of every tag with the name
+    fn tag_values<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.tags
+            .iter()
+            .filter(move |tag| tag.first().map(String::as_str) == Some(name))
+            .filter_map(|tag| tag.get(1))
+            .map(String::as_str)
+    }
+}
+
+/// A Nostr event imported by a `NostrBridge`, ready to be made into a record
+#[derive(Debug, Clone)]
+pub struct NostrImport {
+    /// The id of the Nostr event
+    pub nostr_id: [u8; 32],
+
+    /// The address of the record. This is deterministic, so that importing
+    /// the same event again makes the same address.
+    pub address_data: RecordAddressData,
+
+    /// The time
+    pub timestamp: Timestamp,
+
+    /// The tags, including a `NOSTR_SISTER` tag with the event id
+    pub tag_set: OwnedTagSet,
+
+    /// The payload
+    pub payload: Vec<u8>,
+}
+
+impl NostrImport {
+    /// The `RecordParts` for the record, to be signed with the `signing_data`
+    #[must_use]
+    pub fn record_parts(&self, signing_data: RecordSigningData) -> RecordParts<'_> {
+        RecordParts {
+            signing_data,
+            address_data: self.address_data.clone(),
+            timestamp: self.timestamp,
+            flags: RecordFlags::empty(),
+            tag_set: &self.tag_set,
+            payload: &self.payload,
+        }
+    }
+}
+
+/// Converts between Nostr events and Mosaic records.
+///
+/// Nostr keys and event ids are unrelated to Mosaic keys and references, so
+/// the bridge is told which correspond (`add_key()` and `add_event()`).
+/// Tags that refer to keys or events it does not know are dropped.
+///
+/// Kinds convert as follows:
+///
+/// | Nostr | Mosaic |
+/// |-------|--------|
+/// | 0 (metadata) | `Kind::PROFILE` |
+/// | 1 (text note) | `Kind::MICROBLOG_ROOT` (and from `Kind::REPLY_COMMENT`) |
+/// | 30023 (long-form) | `Kind::BLOG_POST`, as a `BlogPost` with the `d` tag as its slug |
+///
+/// Mosaic has no kind for contact lists (kind 3), so `import_contacts()`
+/// returns the Mosaic keys they follow instead.
+#[derive(Debug, Clone, Default)]
+pub struct NostrBridge {
+    keys: HashMap<[u8; 32], PublicKey>,
+    nostr_keys: HashMap<PublicKey, [u8; 32]>,
+    events: HashMap<[u8; 32], (Reference, Kind)>,
+    nostr_events: HashMap<Reference, [u8; 32]>,
+}
+
+impl NostrBridge {
+    /// Create a `NostrBridge` that knows no keys or events
+    #[must_use]
+    pub fn new() -> NostrBridge {
+        NostrBridge::default()
+    }
+
+    /// Record that the Nostr public key belongs to the Mosaic user
+    pub fn add_key(&mut self, nostr_key: [u8; 32], public_key: PublicKey) {
+        let _ = self.keys.insert(nostr_key, public_key);
+        let _ = self.nostr_keys.insert(public_key, nostr_key);
+    }
+
+    /// Record that the Nostr event is the record with the reference, of the
+    /// `Kind`
+    pub fn add_event(&mut self, nostr_id: [u8; 32], reference: Reference, kind: Kind) {
+        let _ = self.events.insert(nostr_id, (reference, kind));
+        let _ = self.nostr_events.insert(reference, nostr_id);
+    }
+
+    /// Convert a Nostr event (kind 0, 1 or 30023) to the parts of a record
+    ///
+    /// `p` tags become `NOTIFY_PUBLIC_KEY` tags, `e` tags become `REPLY` and
+    /// `ROOT` tags (per NIP-10), `t` tags become `HASHTAG` tags, and `subject`
+    /// and `content-warning` tags become `SUBJECT` and `CONTENT_WARNING` tags.
+    /// The `title`, `summary`, `image` and `published_at` tags of long-form
+    /// events go into the `BlogPost`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the event does not verify, if its author's key is
+    /// not known, if its kind is not one of those, or if its content or tags
+    /// are invalid.
+    pub fn import(&self, event: &NostrEvent) -> Result<NostrImport, Error> {
+        event.verify()?;
+        let nostr_id = from_hex::<32>(&event.id)?;
+        let author = self.author(event)?;
+        let timestamp = Timestamp::from_unixtime(event.created_at, 0)?;
+
+        let mut tag_set = OwnedTagSet::new();
+        tag_set.add_tag(&OwnedTag::new_nostr_sister(&nostr_id));
+
+        let (address_data, payload) = match event.kind {
+            0 => {
+                let payload = profile_from_json(&event.content)?.to_cbor_bytes();
+                (
+                    RecordAddressData::Deterministic(author, Kind::PROFILE, vec![]),
+                    payload,
+                )
+            }
+            1 => (
+                RecordAddressData::Deterministic(author, Kind::MICROBLOG_ROOT, nostr_id.to_vec()),
+                event.content.clone().into_bytes(),
+            ),
+            30023 => {
+                let post = blog_post_from_event(event)?;
+                for tag in post.tags()? {
+                    tag_set.add_tag(&tag);
+                }
+                (
+                    RecordAddressData::Deterministic(
+                        author,
+                        Kind::BLOG_POST,
+                        post.slug.as_bytes().to_vec(),
+                    ),
+                    post.body.into_bytes(),
+                )
+            }
+            kind => return Err(InnerError::UnsupportedNostrKind(kind).into()),
+        };
+
+        if event.kind != 0 {
+            self.import_tags(event, &mut tag_set)?;
+        }
+
+        Ok(NostrImport {
+            nostr_id,
+            address_data,
+            timestamp,
+            tag_set,
+            payload,
+        })
+    }
+
+    /// The Mosaic keys followed by a Nostr contact list (kind 3). Keys the
+    /// bridge does not know are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the event does not verify or is not a contact list
+    pub fn import_contacts(&self, event: &NostrEvent) -> Result<Vec<PublicKey>, Error> {
+        event.verify()?;
+        if event.kind != 3 {
+            return Err(InnerError::UnsupportedNostrKind(event.kind).into());
+        }
+        Ok(event
+            .tag_values("p")
+            .filter_map(|hex| from_hex::<32>(hex).ok())
+            .filter_map(|key| self.keys.get(&key).copied())
+            .collect())
+    }
+
+    /// Convert a record to an unsigned Nostr event. Sign it with
+    /// `NostrEvent::sign()`.
+    ///
+    /// The author's Nostr key is the record's signing key if it is signed by
+    /// a Nostr key, or else must be known to the bridge.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the record is of a kind with no Nostr equivalent, if
+    /// the author's Nostr key is not known, or if the payload is invalid.
+    pub fn export(&self, record: &Record) -> Result<NostrEvent, Error> {
+        let nostr_key = if record.flags().get_signature_scheme() == SignatureScheme::Secp256k1 {
+            *record.signing_key_bytes()
+        } else {
+            *self
+                .nostr_keys
+                .get(&record.author_public_key())
+                .ok_or::<Error>(InnerError::UnmappedNostrKey.into())?
+        };
+
+        let mut tags: Vec<Vec<String>> = Vec::new();
+        let (kind, content) = match record.kind() {
+            Kind::PROFILE => (0, profile_to_json(&Profile::from_record(record)?)),
+            Kind::MICROBLOG_ROOT | Kind::REPLY_COMMENT => {
+                (1, std::str::from_utf8(&record.payload()?)?.to_owned())
+            }
+            Kind::BLOG_POST => {
+                let post = BlogPost::from_record(record)?;
+                tags.extend(blog_post_tags(&post));
+                (30023, post.body)
+            }
+            _ => return Err(InnerError::WrongKind.into()),
+        };
+
+        for tag in record.tag_set() {
+            let Ok(parsed) = tag.parse() else {
+                continue;
+            };
+            let tag = match parsed {
+                ParsedTag::NotifyPublicKey(public_key) => self
+                    .nostr_keys
+                    .get(&public_key)
+                    .map(|key| vec!["p".to_owned(), to_hex(key)]),
+                ParsedTag::Reply { reference, .. } => self
+                    .nostr_events
+                    .get(&reference)
+                    .map(|id| e_tag(id, "reply")),
+                ParsedTag::Root { reference, .. } => self
+                    .nostr_events
+                    .get(&reference)
+                    .map(|id| e_tag(id, "root")),
+                ParsedTag::Hashtag(hashtag) => Some(vec!["t".to_owned(), hashtag.to_owned()]),
+                // A long-form title is already in the title tag
+                ParsedTag::Subject(subject) if kind != 30023 => {
+                    Some(vec!["subject".to_owned(), subject.to_owned()])
+                }
+                ParsedTag::ContentWarning(reason) => {
+                    Some(vec!["content-warning".to_owned(), reason.to_owned()])
+                }
+                _ => None,
+            };
+            tags.extend(tag);
+        }
+
+        let mut event = NostrEvent {
+            id: String::new(),
+            pubkey: to_hex(&nostr_key),
+            created_at: record.timestamp().to_unixtime().0,
+            kind,
+            tags,
+            content,
+            sig: None,
+        };
+        event.id = to_hex(&event.compute_id());
+        Ok(event)
+    }
+
+    fn author(&self, event: &NostrEvent) -> Result<PublicKey, Error> {
+        self.keys
+            .get(&from_hex::<32>(&event.pubkey)?)
+            .copied()
+            .ok_or(InnerError::UnmappedNostrKey.into())
+    }
+
+    fn import_tags(&self, event: &NostrEvent, tag_set: &mut OwnedTagSet) -> Result<(), Error> {
+        for hex in event.tag_values("p") {
+            if let Some(public_key) = from_hex::<32>(hex).ok().and_then(|k| self.keys.get(&k)) {
+                tag_set.add_tag(&OwnedTag::new_notify_public_key(public_key));
+            }
+        }
+
+        // NIP-10: marked e tags, or else the first is the root and the last
+        // is the reply
+        let e_tags: Vec<&Vec<String>> = event
+            .tags
+            .iter()
+            .filter(|tag| tag.first().map(String::as_str) == Some("e") && tag.len() >= 2)
+            .collect();
+        let marked = |marker: &str| {
+            e_tags
+                .iter()
+                .find(|tag| tag.get(3).map(String::as_str) == Some(marker))
+                .map(|tag| tag[1].as_str())
+        };
+        let (root, reply) = if e_tags.iter().any(|tag| tag.len() >= 4) {
+            let root = marked("root");
+            (root, marked("reply").or(root))
+        } else {
+            (
+                e_tags.first().map(|tag| tag[1].as_str()),
+                e_tags.last().map(|tag| tag[1].as_str()),
+            )
+        };
+        if let Some((reference, kind)) = reply.and_then(|id| self.event(id)) {
+            tag_set.add_tag(&OwnedTag::new_reply(reference, *kind));
+        }
+        if let Some((reference, kind)) = root.and_then(|id| self.event(id)) {
+            tag_set.add_tag(&OwnedTag::new_root(reference, *kind));
+        }
+
+        for hashtag in event.tag_values("t") {
+            if let Ok(tag) = OwnedTag::new_hashtag(hashtag) {
+                tag_set.add_tag(&tag);
+            }
+        }
+        if let Some(subject) = event.tag_value("subject") {
+            tag_set.add_tag(&OwnedTag::new_subject(subject)?);
+        }
+        if let Some(tag) = event
+            .tags
+            .iter()
+            .find(|tag| tag.first().map(String::as_str) == Some("content-warning"))
+        {
+            let reason = tag.get(1).map(String::as_str).unwrap_or_default();
+            tag_set.add_tag(&OwnedTag::new_content_warning(reason)?);
+        }
+        Ok(())
+    }
+
+    fn event(&self, hex: &str) -> Option<&(Reference, Kind)> {
+        self.events.get(&from_hex::<32>(hex).ok()?)
+    }
+}
+
+// NIP-23 long-form content to a BlogPost
+fn blog_post_from_event(event: &NostrEvent) -> Result<BlogPost, Error> {
+    let mut post = BlogPost::new(
+        event.tag_value("d").unwrap_or_default(),
+        event.tag_value("title").unwrap_or_default(),
+        &event.content,
+    );
+    post.summary = event.tag_value("summary").map(str::to_owned);
+    post.cover_image = event
+        .tag_value("image")
+        .map(|url| CoverImage::Url(url.to_owned()));
+    post.published_at = match event.tag_value("published_at") {
+        Some(secs) => Some(Timestamp::from_unixtime(secs.parse()?, 0)?),
+        None => None,
+    };
+    Ok(post)
+}
+
+// The NIP-23 tags for a BlogPost. Blob cover images have no URL, so are left
+// out.
+fn blog_post_tags(post: &BlogPost) -> Vec<Vec<String>> {
+    let mut tags = vec![
+        vec!["d".to_owned(), post.slug.clone()],
+        vec!["title".to_owned(), post.title.clone()],
+    ];
+    if let Some(summary) = &post.summary {
+        tags.push(vec!["summary".to_owned(), summary.clone()]);
+    }
+    if let Some(CoverImage::Url(url)) = &post.cover_image {
+        tags.push(vec!["image".to_owned(), url.clone()]);
+    }
+    if let Some(published_at) = post.published_at {
+        tags.push(vec![
+            "published_at".to_owned(),
+            published_at.to_unixtime().0.to_string(),
+        ]);
+    }
+    tags
+}
+
+fn e_tag(id: &[u8; 32], marker: &str) -> Vec<String> {
+    vec!["e".to_owned(), to_hex(id), String::new(), marker.to_owned()]
+}
+
+// NIP-01 metadata to a Profile. Pictures are URLs in Nostr but inline in
+// Mosaic, so they are not carried over.
+fn profile_from_json(content: &str) -> Result<Profile, Error> {
+    let metadata: serde_json::Value = serde_json::from_str(content)?;
+    let text = |name: &str| {
+        metadata
+            .get(name)
+            .and_then(|v| v.as_str())
+            .map(str::to_owned)
+    };
+    let mut profile = Profile::new(&text("name").unwrap_or_default());
+    profile.display_name = text("display_name");
+    profile.about = text("about");
+    profile.website = text("website");
+    profile.lud16 = text("lud16");
+    profile.bot = metadata.get("bot").and_then(serde_json::Value::as_bool);
+    Ok(profile)
+}
+
+fn profile_to_json(profile: &Profile) -> String {
+    let mut metadata = serde_json::Map::new();
+    let _ = metadata.insert("name".to_owned(), profile.name.clone().into());
+    for (name, value) in [
+        ("display_name", &profile.display_name),
+        ("about", &profile.about),
+        ("website", &profile.website),
+        ("lud16", &profile.lud16),
+    ] {
+        if let Some(value) = value {
+            let _ = metadata.insert(name.to_owned(), value.clone().into());
+        }
+    }
+    if let Some(bot) = profile.bot {
+        let _ = metadata.insert("bot".to_owned(), bot.into());
+    }
+    serde_json::Value::Object(metadata).to_string()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    use std::fmt::Write;
+    bytes.iter().fold(String::new(), |mut s, b| {
+        let _ = write!(s, "{b:02x}");
+        s
+    })
+}
+
+fn from_hex<const N: usize>(hex: &str) -> Result<[u8; N], Error> {
+    // from_str_radix() alone would accept a sign
+    if hex.len() != N * 2 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(InnerError::InvalidNostrEvent.into());
+    }
+    let mut bytes = [0_u8; N];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        let pair = hex
+            .get(i * 2..i * 2 + 2)
+            .ok_or::<Error>(InnerError::InvalidNostrEvent.into())?;
+        *byte =
+            u8::from_str_radix(pair, 16).map_err(|_| InnerError::InvalidNostrEvent.into_err())?;
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::*;
+
+    fn generate() -> (SchnorrSigningKey, [u8; 32]) {
+        let key = SchnorrSigningKey::random(&mut scrypt::password_hash::rand_core::OsRng);
+        let bytes = key.verifying_key().to_bytes().into();
+        (key, bytes)
+    }
+
+    fn event(
+        key: &SchnorrSigningKey,
+        kind: u32,
+        tags: Vec<Vec<&str>>,
+        content: &str,
+    ) -> NostrEvent {
+        let mut event = NostrEvent {
+            id: String::new(),
+            pubkey: String::new(),
+            created_at: 1_700_000_000,
+            kind,
+            tags: tags
+                .into_iter()
+                .map(|tag| tag.into_iter().map(str::to_owned).collect())
+                .collect(),
+            content: content.to_owned(),
+            sig: None,
+        };
+        event.sign(key).unwrap();
+        event
+    }
+
+    #[test]
+    fn test_nostr_event() {
+        let (key, _) = generate();
+        let event = event(&key, 1, vec![vec!["t", "nostr"]], "Hello \"Nostr\"\n");
+        event.verify().unwrap();
+
+        let event2 = NostrEvent::from_json(&event.to_json()).unwrap();
+        assert_eq!(event2, event);
+
+        let mut tampered = event.clone();
+        tampered.content = "Goodbye".to_owned();
+        assert!(tampered.verify().is_err());
+
+        assert_eq!(from_hex::<2>("0aFf").unwrap(), [0x0a, 0xff]);
+        assert!(from_hex::<2>("+aff").is_err());
+        assert!(from_hex::<2>("0a+f").is_err());
+        assert!(from_hex::<2>("0a f").is_err());
+        assert!(from_hex::<1>("é").is_err());
+    }
+
+    #[test]
+    fn test_nostr_bridge() {
+        let secret_key = SecretKey::generate();
+        let (key, nostr_key) = generate();
+        let (friend_key, friend_nostr_key) = generate();
+        let friend = SecretKey::generate().public();
+
+        let mut bridge = NostrBridge::new();
+        bridge.add_key(nostr_key, secret_key.public());
+        bridge.add_key(friend_nostr_key, friend);
+
+        // Import a note
+        let note = event(&friend_key, 1, vec![], "First!");
+        bridge.add_event(
+            from_hex(&note.id).unwrap(),
+            Address::new_random(friend, Kind::MICROBLOG_ROOT).to_reference(),
+            Kind::MICROBLOG_ROOT,
+        );
+        let friend_hex = to_hex(&friend_nostr_key);
+        let reply = event(
+            &key,
+            1,
+            vec![
+                vec!["e", &note.id, "", "root"],
+                vec!["p", &friend_hex],
+                vec!["t", "Intro"],
+            ],
+            "Welcome",
+        );
+        let import = bridge.import(&reply).unwrap();
+        let record = OwnedRecord::new(
+            &import.record_parts(RecordSigningData::SecretKey(secret_key.clone())),
+        )
+        .unwrap();
+        assert_eq!(record.kind(), Kind::MICROBLOG_ROOT);
+        assert_eq!(record.payload_bytes(), b"Welcome");
+        let tags: Vec<ParsedTag<'_>> = record
+            .tag_set()
+            .iter()
+            .map(|t| t.parse().unwrap())
+            .collect();
+        let (note_reference, _) = bridge.events[&from_hex::<32>(&note.id).unwrap()];
+        assert_eq!(
+            tags,
+            vec![
+                ParsedTag::NostrSister(from_hex(&reply.id).unwrap()),
+                ParsedTag::NotifyPublicKey(friend),
+                ParsedTag::Reply {
+                    reference: note_reference,
+                    kind: Kind::MICROBLOG_ROOT
+                },
+                ParsedTag::Root {
+                    reference: note_reference,
+                    kind: Kind::MICROBLOG_ROOT
+                },
+                ParsedTag::Hashtag("intro"),
+            ]
+        );
+
+        // And back
+        let exported = bridge.export(&record).unwrap();
+        assert_eq!(exported.kind, 1);
+        assert_eq!(exported.pubkey, reply.pubkey);
+        assert_eq!(exported.created_at, reply.created_at);
+        assert_eq!(exported.content, "Welcome");
+        assert!(exported
+            .tags
+            .contains(&vec!["p".to_owned(), friend_hex.clone()]));
+        assert!(exported
+            .tags
+            .contains(&e_tag(&from_hex(&note.id).unwrap(), "reply")));
+        assert_eq!(exported.id, to_hex(&exported.compute_id()));
+        assert!(exported.sig.is_none());
+    }
+
+    #[test]
+    fn test_nostr_bridge_kinds() {
+        let secret_key = SecretKey::generate();
+        let (key, nostr_key) = generate();
+        let (_, friend_nostr_key) = generate();
+        let friend_hex = to_hex(&friend_nostr_key);
+        let friend = SecretKey::generate().public();
+
+        let mut bridge = NostrBridge::new();
+        bridge.add_key(nostr_key, secret_key.public());
+        bridge.add_key(friend_nostr_key, friend);
+
+        // Profiles
+        let metadata = event(
+            &key,
+            0,
+            vec![],
+            r#"{"name":"alice","about":"hi","picture":"x"}"#,
+        );
+        let import = bridge.import(&metadata).unwrap();
+        let record = OwnedRecord::new(
+            &import.record_parts(RecordSigningData::SecretKey(secret_key.clone())),
+        )
+        .unwrap();
+        let profile = Profile::from_record(&record).unwrap();
+        assert_eq!(profile.name, "alice");
+        assert_eq!(profile.about.as_deref(), Some("hi"));
+        assert_eq!(
+            bridge.export(&record).unwrap().content,
+            r#"{"about":"hi","name":"alice"}"#
+        );
+
+        // Long-form, addressed by the d tag
+        let article = event(
+            &key,
+            30023,
+            vec![vec!["d", "intro"], vec!["title", "Intro"]],
+            "# Hi",
+        );
+        let import = bridge.import(&article).unwrap();
+        assert_eq!(
+            import.address_data.address(),
+            Address::new_deterministic(secret_key.public(), Kind::BLOG_POST, b"intro")
+        );
+        let record = OwnedRecord::new(
+            &import.record_parts(RecordSigningData::SecretKey(secret_key.clone())),
+        )
+        .unwrap();
+        let post = BlogPost::from_record(&record).unwrap();
+        assert_eq!(post, BlogPost::new("intro", "Intro", "# Hi"));
+        let exported = bridge.export(&record).unwrap();
+        assert_eq!(exported.content, "# Hi");
+        assert!(exported
+            .tags
+            .contains(&vec!["d".to_owned(), "intro".to_owned()]));
+        assert!(exported
+            .tags
+            .contains(&vec!["title".to_owned(), "Intro".to_owned()]));
+
+        // The exported article imports to the same address
+        let mut exported = exported;
+        exported.sign(&key).unwrap();
+        assert_eq!(
+            bridge.import(&exported).unwrap().address_data.address(),
+            record.address()
+        );
+
+        // Contact lists
+        let contacts = event(&key, 3, vec![vec!["p", &friend_hex]], "");
+        assert!(bridge.import(&contacts).is_err());
+        assert_eq!(bridge.import_contacts(&contacts).unwrap(), vec![friend]);
+
+        // Unknown authors
+        let (stranger_key, _) = generate();
+        let note = event(&stranger_key, 1, vec![], "Hi");
+        let e = bridge.import(&note).unwrap_err();
+        assert!(matches!(e.inner, InnerError::UnmappedNostrKey));
+    }
+}
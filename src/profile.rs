@@ -1,8 +1,6 @@
-use crate::{
-    Error, InnerError, Kind, OwnedRecord, OwnedTagSet, Record, RecordAddressData, RecordFlags,
-    RecordParts, RecordSigningData, SecretKey, Timestamp,
-};
+use crate::{Error, InnerError, Kind, OwnedRecord, PublicKey, Record, RecordBuilder, Signer};
 use minicbor_derive::{Decode, Encode};
+use std::sync::Arc;
 
 /// A user Profile
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Encode, Decode)]
@@ -79,28 +77,25 @@ impl Profile {
         Ok(minicbor::decode(cbor)?)
     }
 
-    /// Create a new `OwnedRecord` based on this `Profile`
+    /// Create a new `OwnedRecord` based on this `Profile` of the author,
+    /// signed by the author's `Signer` (such as their `SecretKey`, or a
+    /// subkey's)
+    ///
+    /// With the `zstd` feature the payload is compressed if that saves space,
+    /// which helps profiles with inline images fit.
     ///
     /// # Errors
     ///
-    /// This is unlikely to return an error.
-    pub fn as_record(&self, secret_key: SecretKey) -> Result<OwnedRecord, Error> {
-        let payload = self.to_cbor_bytes();
-        let tag_set = OwnedTagSet::new();
-
-        let public_key = secret_key.public();
-        let parts = RecordParts {
-            signing_data: RecordSigningData::SecretKey(secret_key),
-            address_data: RecordAddressData::Random(public_key, Kind::PROFILE),
-            timestamp: Timestamp::now()?,
-            flags: RecordFlags::empty(),
-            tag_set: &tag_set,
-            payload: &payload,
-        };
-
-        let record = OwnedRecord::new(&parts)?;
-
-        Ok(record)
+    /// Returns an error if the profile is too large to fit in a record, or if
+    /// signing fails.
+    pub fn as_record<S: Signer + 'static>(
+        &self,
+        author: PublicKey,
+        signer: S,
+    ) -> Result<OwnedRecord, Error> {
+        RecordBuilder::new_with_signer(Kind::PROFILE, author, Arc::new(signer))
+            .payload(&self.to_cbor_bytes())?
+            .sign()
     }
 
     /// Extract a `Profile` from a `Profile` `Record`
@@ -117,7 +112,7 @@ impl Profile {
             return Err(InnerError::WrongKind.into());
         }
 
-        Profile::from_cbor_bytes(record.payload_bytes())
+        Profile::from_cbor_bytes(&record.payload()?)
     }
 }
 
@@ -142,7 +137,7 @@ mod test {
 
         // Convert to and from a Record
         let secret_key = SecretKey::generate();
-        let record = profile.as_record(secret_key).unwrap();
+        let record = profile.as_record(secret_key.public(), secret_key).unwrap();
         let profile3 = Profile::from_record(&record).unwrap();
         assert_eq!(profile2, profile3);
     }
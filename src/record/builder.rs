@@ -0,0 +1,375 @@
+use super::full_len;
+use crate::{
+    AsyncSigner, Error, InnerError, Kind, OwnedRecord, OwnedTagSet, PublicKey, RecordAddressData,
+    RecordFlags, RecordParts, RecordSigningData, SchnorrSigningKey, SecretKey, Signer, Tag,
+    Timestamp, UnsignedRecord,
+};
+use std::sync::Arc;
+
+/// A builder that assembles and signs an `OwnedRecord`.
+///
+/// Limits are checked as data is added, so an oversized tag set or payload
+/// is reported where it is added rather than when signing.
+///
+/// With the `zstd` feature, the payload is compressed when signing if that
+/// makes the record smaller, and `RecordFlags::ZSTD` is set.
+///
+/// ```
+/// # use mosaic_core::{Kind, OwnedTag, RecordBuilder, SecretKey, Timestamp};
+/// let secret_key = SecretKey::generate();
+/// let mentioned = SecretKey::generate().public();
+/// let record = RecordBuilder::new(Kind::MICROBLOG_ROOT, secret_key)
+///     .tag(&OwnedTag::new_notify_public_key(&mentioned))
+///     .unwrap()
+///     .payload(b"Hello World!")
+///     .unwrap()
+///     .timestamp(Timestamp::from_unixtime(1_700_000_000, 0).unwrap())
+///     .sign()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct RecordBuilder {
+    signing_data: RecordSigningData,
+    author_public_key: PublicKey,
+    kind: Kind,
+    deterministic_key: Option<Vec<u8>>,
+    timestamp: Option<Timestamp>,
+    flags: RecordFlags,
+    tag_set: OwnedTagSet,
+    payload: Vec<u8>,
+    #[cfg(feature = "zstd")]
+    compress: bool,
+}
+
+impl RecordBuilder {
+    /// Start building a record of the given `Kind`, to be signed with the
+    /// `SecretKey`.
+    ///
+    /// The author defaults to the public key of the `SecretKey`. Use `author()`
+    /// when signing with a subkey.
+    #[must_use]
+    pub fn new(kind: Kind, secret_key: SecretKey) -> RecordBuilder {
+        let author_public_key = secret_key.public();
+        Self::with_signing_data(
+            kind,
+            author_public_key,
+            RecordSigningData::SecretKey(secret_key),
+        )
+    }
+
+    /// Start building a record of the given `Kind` by the author, to be signed
+    /// with a secp256k1 `SchnorrSigningKey` (e.g. a nostr key that the author
+    /// has listed as `SubkeyMarker::ActiveNostrKey`).
+    #[must_use]
+    pub fn new_secp256k1(
+        kind: Kind,
+        author_public_key: PublicKey,
+        signing_key: SchnorrSigningKey,
+    ) -> RecordBuilder {
+        Self::with_signing_data(
+            kind,
+            author_public_key,
+            RecordSigningData::Secp256k1SecretKey(signing_key),
+        )
+    }
+
+    /// Start building a record of the given `Kind` by the author, to be signed
+    /// by a `Signer` (e.g. a key agent).
+    #[must_use]
+    pub fn new_with_signer(
+        kind: Kind,
+        author_public_key: PublicKey,
+        signer: Arc<dyn Signer>,
+    ) -> RecordBuilder {
+        Self::with_signing_data(kind, author_public_key, RecordSigningData::Signer(signer))
+    }
+
+    /// Start building a record of the given `Kind` by the author, with any
+    /// `RecordSigningData`. Use `RecordSigningData::PublicKey` to
+    /// `sign_async()` with a key held elsewhere, or to `prepare()` a record
+    /// for signing later.
+    #[must_use]
+    pub fn with_signing_data(
+        kind: Kind,
+        author_public_key: PublicKey,
+        signing_data: RecordSigningData,
+    ) -> RecordBuilder {
+        RecordBuilder {
+            signing_data,
+            author_public_key,
+            kind,
+            deterministic_key: None,
+            timestamp: None,
+            flags: RecordFlags::empty(),
+            tag_set: OwnedTagSet::new(),
+            payload: Vec::new(),
+            #[cfg(feature = "zstd")]
+            compress: true,
+        }
+    }
+
+    /// Set the author (master) `PublicKey`
+    #[must_use]
+    pub fn author(mut self, author_public_key: PublicKey) -> RecordBuilder {
+        self.author_public_key = author_public_key;
+        self
+    }
+
+    /// Add a `Tag`
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the tag set would become too long, or if the record
+    /// would exceed `1_048_576` bytes.
+    pub fn tag(mut self, tag: &Tag) -> Result<RecordBuilder, Error> {
+        self.tag_set.add_tag(tag);
+        self.check_lengths()?;
+        Ok(self)
+    }
+
+    /// Set the payload
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the record would exceed `1_048_576` bytes. When
+    /// the payload may be compressed this check waits until signing.
+    pub fn payload(mut self, payload: &[u8]) -> Result<RecordBuilder, Error> {
+        payload.clone_into(&mut self.payload);
+        self.check_lengths()?;
+        Ok(self)
+    }
+
+    /// Set the `Timestamp`. If not set, the current time is used when signing.
+    #[must_use]
+    pub fn timestamp(mut self, timestamp: Timestamp) -> RecordBuilder {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Set the `RecordFlags`
+    #[must_use]
+    pub fn flags(mut self, flags: RecordFlags) -> RecordBuilder {
+        self.flags = flags;
+        self
+    }
+
+    /// Use a deterministic address derived from `key`, so that later records
+    /// built with the same key (and kind and author) share the same `Address`.
+    ///
+    /// Without this, the address nonce is random.
+    #[must_use]
+    pub fn deterministic_address(mut self, key: &[u8]) -> RecordBuilder {
+        self.deterministic_key = Some(key.to_owned());
+        self
+    }
+
+    /// Whether to compress the payload with Zstd when signing, if that makes
+    /// it smaller. Defaults to `true`.
+    ///
+    /// Compression is never applied if `RecordFlags::ZSTD` is already set
+    /// (i.e. the payload given is already compressed).
+    #[cfg(feature = "zstd")]
+    #[must_use]
+    pub fn compress(mut self, compress: bool) -> RecordBuilder {
+        self.compress = compress;
+        self
+    }
+
+    /// Sign and assemble the `OwnedRecord`
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the timestamp was not set and the current time is
+    /// unavailable, if any data is too long, if reserved flags are set, or if
+    /// signing fails.
+    pub fn sign(self) -> Result<OwnedRecord, Error> {
+        self.assemble(OwnedRecord::new)
+    }
+
+    /// Sign with an `AsyncSigner` (such as a key agent reached over some
+    /// transport) and assemble the `OwnedRecord`. Only the signature scheme
+    /// and signing key of the signing data are used, and the signer must hold
+    /// that key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the timestamp was not set and the current time is
+    /// unavailable, if any data is too long, if reserved flags are set, or if
+    /// signing fails or the signer's signature does not verify.
+    pub async fn sign_async<S: AsyncSigner + ?Sized>(
+        self,
+        signer: &S,
+    ) -> Result<OwnedRecord, Error> {
+        self.prepare()?.sign(signer).await
+    }
+
+    /// Assemble an `UnsignedRecord`, to be signed later. Only the signature
+    /// scheme and signing key of the signing data are used.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the timestamp was not set and the current time is
+    /// unavailable, if any data is too long, or if reserved flags are set.
+    pub fn prepare(self) -> Result<UnsignedRecord, Error> {
+        self.assemble(UnsignedRecord::new)
+    }
+
+    fn assemble<T>(self, f: impl FnOnce(&RecordParts) -> Result<T, Error>) -> Result<T, Error> {
+        let timestamp = match self.timestamp {
+            Some(timestamp) => timestamp,
+            None => Timestamp::now()?,
+        };
+
+        #[allow(unused_mut)]
+        let mut flags = self.flags;
+
+        #[cfg(feature = "zstd")]
+        let payload = if self.compress && !flags.contains(RecordFlags::ZSTD) {
+            if let Some(compressed) = super::compression::compress_if_smaller(&self.payload)? {
+                flags |= RecordFlags::ZSTD;
+                compressed
+            } else {
+                self.payload
+            }
+        } else {
+            self.payload
+        };
+
+        #[cfg(not(feature = "zstd"))]
+        let payload = self.payload;
+
+        let address_data = match self.deterministic_key {
+            Some(key) => RecordAddressData::Deterministic(self.author_public_key, self.kind, key),
+            None => RecordAddressData::Random(self.author_public_key, self.kind),
+        };
+
+        f(&RecordParts {
+            signing_data: self.signing_data,
+            address_data,
+            timestamp,
+            flags,
+            tag_set: &self.tag_set,
+            payload: &payload,
+        })
+    }
+
+    fn check_lengths(&self) -> Result<(), Error> {
+        let tags_len = self.tag_set.as_bytes().len();
+        if tags_len > u16::MAX as usize {
+            return Err(InnerError::RecordTooLong.into());
+        }
+
+        #[cfg(feature = "zstd")]
+        if self.compress && !self.flags.contains(RecordFlags::ZSTD) {
+            return Ok(());
+        }
+
+        // An unsupported signature scheme is reported when signing
+        let sig_len = self.signing_data.signature_len().unwrap_or(0);
+        if full_len(tags_len, self.payload.len(), sig_len) > 1_048_576 {
+            return Err(InnerError::RecordTooLong.into());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn test_record_builder() {
+        let secret_key = SecretKey::generate();
+        let public_key = secret_key.public();
+        let mentioned = SecretKey::generate().public();
+        let timestamp = Timestamp::from_unixtime(1_700_000_000, 0).unwrap();
+        let tag = OwnedTag::new_notify_public_key(&mentioned);
+
+        let record = RecordBuilder::new(Kind::MICROBLOG_ROOT, secret_key.clone())
+            .tag(&tag)
+            .unwrap()
+            .payload(b"Hello World!")
+            .unwrap()
+            .timestamp(timestamp)
+            .flags(RecordFlags::FROM_AUTHOR)
+            .deterministic_address(b"greeting")
+            .sign()
+            .unwrap();
+
+        record.verify().unwrap();
+        assert_eq!(record.kind(), Kind::MICROBLOG_ROOT);
+        assert_eq!(record.author_public_key(), public_key);
+        assert_eq!(record.signing_public_key(), Some(public_key));
+        assert_eq!(record.timestamp(), timestamp);
+        assert_eq!(record.flags(), RecordFlags::FROM_AUTHOR);
+        assert_eq!(record.payload_bytes(), b"Hello World!");
+        assert_eq!(record.tag_set().iter().next(), Some(&*tag));
+        assert_eq!(
+            record.address(),
+            Address::new_deterministic(public_key, Kind::MICROBLOG_ROOT, b"greeting")
+        );
+
+        // The same deterministic key gives the same address
+        let record2 = RecordBuilder::new(Kind::MICROBLOG_ROOT, secret_key)
+            .timestamp(timestamp)
+            .deterministic_address(b"greeting")
+            .sign()
+            .unwrap();
+        assert_eq!(record.address(), record2.address());
+    }
+
+    #[test]
+    fn test_record_builder_limits() {
+        let secret_key = SecretKey::generate();
+
+        let big_tag = OwnedTag::new(TagType(100), &vec![7_u8; 40_000]).unwrap();
+        let builder = RecordBuilder::new(Kind::EXAMPLE, secret_key.clone())
+            .tag(&big_tag)
+            .unwrap();
+        let e = builder.tag(&big_tag).unwrap_err();
+        assert!(matches!(e.inner, InnerError::RecordTooLong));
+
+        let builder = RecordBuilder::new(Kind::EXAMPLE, secret_key);
+        #[cfg(feature = "zstd")]
+        let builder = builder.compress(false);
+        let e = builder.payload(&vec![0_u8; 1_048_576]).unwrap_err();
+        assert!(matches!(e.inner, InnerError::RecordTooLong));
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_record_builder_compress() {
+        let secret_key = SecretKey::generate();
+        let timestamp = Timestamp::from_unixtime(1_700_000_000, 0).unwrap();
+        let payload = "All work and no play makes Jack a dull boy. ".repeat(40_000);
+
+        // Too big uncompressed, but compresses automatically
+        let record = RecordBuilder::new(Kind::BLOG_POST, secret_key.clone())
+            .payload(payload.as_bytes())
+            .unwrap()
+            .timestamp(timestamp)
+            .sign()
+            .unwrap();
+        assert!(record.flags().contains(RecordFlags::ZSTD));
+        assert!(record.payload_len() < payload.len());
+        assert_eq!(&*record.payload().unwrap(), payload.as_bytes());
+
+        // Unless compression is turned off
+        assert!(RecordBuilder::new(Kind::BLOG_POST, secret_key.clone())
+            .compress(false)
+            .payload(payload.as_bytes())
+            .is_err());
+
+        // Payloads that don't shrink are left alone
+        let record = RecordBuilder::new(Kind::BLOG_POST, secret_key)
+            .payload(b"tiny")
+            .unwrap()
+            .timestamp(timestamp)
+            .sign()
+            .unwrap();
+        assert!(!record.flags().contains(RecordFlags::ZSTD));
+        assert_eq!(record.payload_bytes(), b"tiny");
+        assert_eq!(&*record.payload().unwrap(), b"tiny");
+    }
+}
@@ -0,0 +1,57 @@
+use crate::{Error, InnerError};
+use std::io::Read;
+
+// Compress a payload with Zstd, returning the compressed bytes only if they
+// take up less (padded) space in a record than the uncompressed bytes.
+pub(crate) fn compress_if_smaller(payload: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+    // Zstd framing overhead means tiny payloads never shrink
+    if payload.len() < 64 {
+        return Ok(None);
+    }
+
+    let compressed = zstd::bulk::compress(payload, 0)?;
+    if padded_len!(compressed.len()) < padded_len!(payload.len()) {
+        Ok(Some(compressed))
+    } else {
+        Ok(None)
+    }
+}
+
+// Decompress a Zstd payload, refusing to produce more than `max_len` bytes.
+//
+// The output is grown as data is decoded rather than allocated up front, so a
+// small malicious payload cannot claim a huge buffer.
+pub(crate) fn decompress(compressed: &[u8], max_len: usize) -> Result<Vec<u8>, Error> {
+    let decoder = zstd::stream::read::Decoder::with_buffer(compressed)?;
+    let mut output: Vec<u8> = Vec::with_capacity((compressed.len() * 4).min(max_len));
+    let _ = decoder.take(max_len as u64 + 1).read_to_end(&mut output)?;
+    if output.len() > max_len {
+        return Err(InnerError::DecompressedPayloadTooLong(max_len).into());
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_compression() {
+        // Small payloads are left alone
+        assert!(compress_if_smaller(b"Hello World!").unwrap().is_none());
+
+        let payload = "la ".repeat(10_000);
+        let compressed = compress_if_smaller(payload.as_bytes()).unwrap().unwrap();
+        assert!(compressed.len() < payload.len());
+
+        let decompressed = decompress(&compressed, payload.len()).unwrap();
+        assert_eq!(decompressed, payload.as_bytes());
+
+        // A zip bomb is cut off at the limit
+        let e = decompress(&compressed, payload.len() - 1).unwrap_err();
+        assert!(matches!(e.inner, InnerError::DecompressedPayloadTooLong(_)));
+
+        // Garbage is an error
+        assert!(decompress(b"not zstd at all", 1_000).is_err());
+    }
+}
@@ -69,6 +69,13 @@ impl RecordFlags {
         self.0 = (self.0 & !Self::SIG_SCHEME_MASK) | bits;
     }
 
+    /// Whether any bits are set that are neither defined flags nor the
+    /// signature scheme
+    #[must_use]
+    pub fn uses_reserved_bits(&self) -> bool {
+        self.0 & !(Self::all().0 | Self::SIG_SCHEME_MASK) != 0
+    }
+
     /// Get the signature scheme
     #[must_use]
     pub fn get_signature_scheme(&self) -> SignatureScheme {
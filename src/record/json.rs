@@ -1,6 +1,7 @@
 use crate::{
     Address, DuplicateHandling, Error, InnerError, OwnedRecord, OwnedTagSet, PublicKey, ReadAccess,
-    Record, RecordAddressData, RecordFlags, RecordParts, RecordSigningData, Timestamp,
+    Record, RecordAddressData, RecordFlags, RecordParts, RecordSignature, RecordSigningData,
+    SchnorrVerifyingKey, Timestamp,
 };
 use serde::{Deserialize, Serialize};
 
@@ -13,6 +14,10 @@ struct JsonKind {
     duplicate_handling: DuplicateHandling,
     read_access: ReadAccess,
     content_is_printable: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -25,8 +30,12 @@ struct JsonRecord {
     timestamp: u64,
     flags: RecordFlags,
     tags: OwnedTagSet,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tag_descriptions: Vec<String>,
     payload: Option<String>,
     z32_payload: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    payload_description: Option<String>,
     signature: String,
 }
 
@@ -49,11 +58,15 @@ impl Record {
 
     #[allow(clippy::cast_sign_loss)]
     fn as_json_record(&self) -> JsonRecord {
+        // Compressed payloads are exported raw so that the record round-trips
+        let printable =
+            self.kind().content_is_printable() && !self.flags().contains(RecordFlags::ZSTD);
+
         JsonRecord {
             id: self.id().as_printable(),
             address: self.address().as_printable(),
             author_key: self.author_public_key().as_printable(),
-            signing_key: self.signing_public_key().as_printable(),
+            signing_key: PublicKey::bytes_as_printable(self.signing_key_bytes()),
             kind: JsonKind {
                 as_number: self.kind().to_u64(),
                 as_bytes: self.kind().to_bytes().to_vec(),
@@ -62,21 +75,25 @@ impl Record {
                 duplicate_handling: self.kind().duplicate_handling(),
                 read_access: self.kind().read_access(),
                 content_is_printable: self.kind().content_is_printable(),
+                name: self.kind().name().map(str::to_owned),
+                description: self.kind().description().map(str::to_owned),
             },
             timestamp: self.timestamp().as_nanoseconds() as u64,
             flags: self.flags(),
             tags: self.tag_set().to_owned(),
-            payload: if self.kind().content_is_printable() {
+            tag_descriptions: self.tag_set().iter().map(ToString::to_string).collect(),
+            payload: if printable {
                 Some(String::from_utf8_lossy(self.payload_bytes()).to_string())
             } else {
                 None
             },
-            z32_payload: if self.kind().content_is_printable() {
+            z32_payload: if printable {
                 None
             } else {
                 Some(z32::encode(self.payload_bytes()))
             },
-            signature: z32::encode(self.signature().to_bytes().as_slice()),
+            payload_description: crate::kind_registry::format_payload(self),
+            signature: z32::encode(self.signature_bytes()),
         }
     }
 }
@@ -99,15 +116,27 @@ impl OwnedRecord {
             Vec::new()
         };
 
-        let r = OwnedRecord::new(&RecordParts {
-            signing_data: RecordSigningData::PublicKeyAndSignature(
+        let signing_data = match RecordSignature::from_bytes(
+            json_record.flags.get_signature_scheme(),
+            &z32::decode(json_record.signature.as_bytes())?,
+        )? {
+            RecordSignature::Ed25519(signature) => RecordSigningData::PublicKeyAndSignature(
                 PublicKey::from_printable(&json_record.signing_key)?,
-                ed25519_dalek::Signature::from_bytes(
-                    z32::decode(json_record.signature.as_bytes())?
-                        .as_slice()
-                        .try_into()?,
-                ),
+                signature,
             ),
+            RecordSignature::Secp256k1(signature) => {
+                RecordSigningData::Secp256k1PublicKeyAndSignature(
+                    SchnorrVerifyingKey::from_bytes(&PublicKey::bytes_from_printable(
+                        &json_record.signing_key,
+                    )?)
+                    .map_err(|e| InnerError::Secp256k1(e).into_err())?,
+                    signature,
+                )
+            }
+        };
+
+        let r = OwnedRecord::new(&RecordParts {
+            signing_data,
             address_data: RecordAddressData::Address(Address::from_printable(
                 &json_record.address,
             )?),
@@ -155,7 +184,7 @@ mod test {
 
         assert_eq!(
             json,
-            r#"{"id":"moref0yyyyyaayyryb3k67amzuz396jk3jjniyapb937on4y58ajzz9qoek7tor3xqdaer8gtens8jgx1or","address":"moref068okurmuk3runyyyybtoyyeyd1f5t9r8btz6r1kwcu3tawyyryqymjbcbd1hd8nwf1iwnaj6q8t31","author_key":"mopub0tqhx3bacp9tr1idr6cqfyybydon4emyehzy3aibcipysnxuthqco","signing_key":"mopub0tqhx3bacp9tr1idr6cqfyybydon4emyehzy3aibcipysnxuthqco","kind":{"as_number":425201827868,"as_bytes":[0,0,0,99,0,1,0,28],"application_id":99,"application_kind":1,"duplicate_handling":"Unique","read_access":"Everybody","content_is_printable":true},"timestamp":425201827868,"flags":0,"tags":[],"payload":"hello world","z32_payload":null,"signature":"hbjsaiwc8d3qnujt3koepuyzydqmfygn4wbpm5bt8baq8imt8pxr46xwhbr13fxx1gd9nkd9g353n8rz1nwbsbjdez9ndgb85uasebo"}"#
+            r#"{"id":"moref0yyyyyaayyryb3k67amzuz396jk3jjniyapb937on4y58ajzz9qoek7tor3xqdaer8gtens8jgx1or","address":"moref068okurmuk3runyyyybtoyyeyd1f5t9r8btz6r1kwcu3tawyyryqymjbcbd1hd8nwf1iwnaj6q8t31","author_key":"mopub0tqhx3bacp9tr1idr6cqfyybydon4emyehzy3aibcipysnxuthqco","signing_key":"mopub0tqhx3bacp9tr1idr6cqfyybydon4emyehzy3aibcipysnxuthqco","kind":{"as_number":425201827868,"as_bytes":[0,0,0,99,0,1,0,28],"application_id":99,"application_kind":1,"duplicate_handling":"Unique","read_access":"Everybody","content_is_printable":true,"name":"Example","description":"An example for documentation and tests"},"timestamp":425201827868,"flags":0,"tags":[],"payload":"hello world","z32_payload":null,"signature":"hbjsaiwc8d3qnujt3koepuyzydqmfygn4wbpm5bt8baq8imt8pxr46xwhbr13fxx1gd9nkd9g353n8rz1nwbsbjdez9ndgb85uasebo"}"#
         );
     }
 
@@ -188,4 +217,33 @@ mod test {
 
         assert_eq!(record, actual_record);
     }
+
+    #[test]
+    fn test_secp256k1_record_json() {
+        let author_public_key = SecretKey::generate().public();
+        let signing_key = SchnorrSigningKey::random(&mut scrypt::password_hash::rand_core::OsRng);
+        let record = RecordBuilder::new_secp256k1(Kind::EXAMPLE, author_public_key, signing_key)
+            .payload(b"hello world")
+            .unwrap()
+            .timestamp(Timestamp::from_nanoseconds(425_201_827_868).unwrap())
+            .sign()
+            .unwrap();
+
+        let record2 = OwnedRecord::from_json(&record.as_json()).unwrap();
+        assert_eq!(record, record2);
+    }
+
+    #[test]
+    fn test_record_json_tag_descriptions() {
+        let record = RecordBuilder::new(Kind::EXAMPLE, SecretKey::generate())
+            .tag(&OwnedTag::new_hashtag("rust").unwrap())
+            .unwrap()
+            .timestamp(Timestamp::from_nanoseconds(425_201_827_868).unwrap())
+            .sign()
+            .unwrap();
+
+        let json = record.as_json();
+        assert!(json.contains(r#""tag_descriptions":["Hashtag: #rust"]"#));
+        assert_eq!(OwnedRecord::from_json(&json).unwrap(), record);
+    }
 }
@@ -1,16 +1,34 @@
 use crate::{
-    Address, Blake3, Error, Id, InnerError, Kind, PublicKey, SecretKey, TagSet, Timestamp,
+    Address, Blake3, Error, Id, InnerError, KeySchedule, Kind, PublicKey, RecordSignature,
+    SchnorrSignature, SchnorrSigningKey, SchnorrVerifyingKey, SecretKey, Signer, SignerIdentity,
+    TagSet, Timestamp,
 };
 use ed25519_dalek::Signature;
+use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::ops::{Deref, DerefMut, Range};
+use std::sync::Arc;
 
 #[cfg(feature = "json")]
 mod json;
 
+mod batch;
+
+mod builder;
+pub use builder::RecordBuilder;
+
+#[cfg(feature = "zstd")]
+mod compression;
+
 mod flags;
 pub use flags::{RecordFlags, SignatureScheme};
 
+mod unsigned;
+pub use unsigned::UnsignedRecord;
+
+mod stream;
+pub use stream::{AsyncRecordReader, AsyncRecordWriter, RecordReader, RecordWriter};
+
 /// A `Record` is a digitally signed datum generated by a user,
 /// stored in and retrieed from a server, and used by an application,
 /// and unsized (borrowed).
@@ -63,8 +81,8 @@ impl Record {
     ///
     /// # Errors
     ///
-    /// Errors if the input is not long enough or if the length is more than `1_048_576`
-    /// bytes.
+    /// Errors if the input is not long enough for the lengths given in the header,
+    /// or if the length is more than `1_048_576` bytes.
     ///
     /// # Safety
     ///
@@ -75,13 +93,13 @@ impl Record {
         if input.len() < HEADER_LEN {
             return Err(InnerError::EndOfInput.into());
         }
-        let unpadded_tags_len = u16::from_le_bytes(input[LEN_T_RANGE].try_into().unwrap()) as usize;
-        let unpadded_payload_len =
-            u32::from_le_bytes(input[LEN_P_RANGE].try_into().unwrap()) as usize;
-        let len = full_len(unpadded_tags_len, unpadded_payload_len, 64);
+        let len = full_len_from_header(&input[..HEADER_LEN]);
         if len > 1_048_576 {
             return Err(InnerError::RecordTooLong.into());
         }
+        if input.len() < len {
+            return Err(InnerError::EndOfInput.into());
+        }
 
         let unverified = Self::from_inner(&input[..len]);
         Ok(unverified)
@@ -93,6 +111,12 @@ impl Record {
         &self.0
     }
 
+    /// Copy to an allocated owned data type
+    #[must_use]
+    pub fn to_owned(&self) -> OwnedRecord {
+        OwnedRecord(self.0.to_owned())
+    }
+
     /// Write a `Record` to the buffer, assembled from the `parts`
     ///
     /// # Errors
@@ -103,8 +127,49 @@ impl Record {
         buffer: &'a mut [u8],
         parts: &RecordParts,
     ) -> Result<&'a Record, Error> {
+        let (len, sig_range, hasher) = Self::write_unsigned(buffer, parts)?;
+
+        // Write the signature
+        let sig: RecordSignature = match parts.signing_data {
+            RecordSigningData::SecretKey(ref secret_key) => secret_key.sign_hasher(hasher)?.into(),
+            RecordSigningData::PublicKeyAndSignature(_, signature) => signature.into(),
+            RecordSigningData::Secp256k1SecretKey(ref signing_key) => {
+                crate::signature::schnorr_sign_hasher(signing_key, hasher)?.into()
+            }
+            RecordSigningData::Secp256k1PublicKeyAndSignature(_, signature) => signature.into(),
+            RecordSigningData::Signer(ref signer) => {
+                // Signatures from outside this crate are checked, even in
+                // release builds
+                let digest: [u8; 64] = digest::FixedOutput::finalize_fixed(hasher).into();
+                let sig = signer.sign_record_digest(&digest)?;
+                if sig.scheme() != signer.signature_scheme() {
+                    return Err(InnerError::SignerSchemeMismatch.into());
+                }
+                sig.verify_digest(&signer.signing_key_bytes(), &digest)?;
+                sig
+            }
+            RecordSigningData::PublicKey(_) | RecordSigningData::Secp256k1PublicKey(_) => {
+                return Err(InnerError::MissingSignature.into());
+            }
+        };
+        buffer[sig_range].copy_from_slice(sig.to_bytes().as_slice());
+
+        let record = Record::from_inner(&buffer[..len]);
+        if cfg!(debug_assertions) {
+            record.verify()?;
+        }
+
+        Ok(record)
+    }
+
+    // Write everything but the signature, returning the length of the record,
+    // the range the signature goes in, and the hasher over the signed data
+    fn write_unsigned(
+        buffer: &mut [u8],
+        parts: &RecordParts,
+    ) -> Result<(usize, Range<usize>, Blake3), Error> {
         // Data checks
-        if parts.flags | RecordFlags::all() != RecordFlags::all() {
+        if parts.flags.uses_reserved_bits() {
             return Err(InnerError::ReservedFlagsUsed.into());
         }
         if parts.tag_set.as_bytes().len() > 65_536 {
@@ -120,7 +185,7 @@ impl Record {
         if payload_len > u32::MAX as usize {
             return Err(InnerError::RecordTooLong.into());
         }
-        let sig_len = 64;
+        let sig_len = parts.signing_data.signature_len()?;
         let len = full_len(tags_len, payload_len, sig_len);
         if len > 1_048_576 {
             return Err(InnerError::RecordTooLong.into());
@@ -143,14 +208,16 @@ impl Record {
 
         // Write LenS
         #[allow(clippy::cast_possible_truncation)]
-        buffer[LEN_S_RANGE].copy_from_slice((64_u16).to_le_bytes().as_slice());
+        buffer[LEN_S_RANGE].copy_from_slice((sig_len as u16).to_le_bytes().as_slice());
 
         // Write LenT
         #[allow(clippy::cast_possible_truncation)]
         buffer[LEN_T_RANGE].copy_from_slice((tags_len as u16).to_le_bytes().as_slice());
 
-        // Write flags
-        buffer[FLAGS_RANGE].copy_from_slice(parts.flags.bits().to_le_bytes().as_slice());
+        // Write flags, with the signature scheme of the signing data
+        let mut flags = parts.flags;
+        flags.set_signature_scheme(parts.signing_data.signature_scheme());
+        buffer[FLAGS_RANGE].copy_from_slice(flags.bits().to_le_bytes().as_slice());
 
         // Write timestamp
         buffer[TIMESTAMP_RANGE].copy_from_slice(parts.timestamp.to_bytes().as_slice());
@@ -159,11 +226,7 @@ impl Record {
         buffer[ADDR_RANGE].copy_from_slice(address.as_bytes().as_slice());
 
         // Write the signing key
-        let signing_key = match parts.signing_data {
-            RecordSigningData::SecretKey(ref secret_key) => secret_key.public(),
-            RecordSigningData::PublicKeyAndSignature(signing_key, _) => signing_key,
-        };
-        buffer[SIGNING_KEY_RANGE].copy_from_slice(signing_key.as_bytes().as_slice());
+        buffer[SIGNING_KEY_RANGE].copy_from_slice(&parts.signing_data.signing_key_bytes());
 
         // Compute the truehash
         let mut truehash: [u8; 64] = [0; 64];
@@ -177,20 +240,11 @@ impl Record {
         buffer[ID_HASH_RANGE].copy_from_slice(&truehash[..40]);
         buffer[ID_TIMESTAMP_RANGE].copy_from_slice(parts.timestamp.to_bytes().as_slice());
 
-        // Write the signature
-        let sig = match parts.signing_data {
-            RecordSigningData::SecretKey(ref secret_key) => secret_key.sign_hasher(hasher)?,
-            RecordSigningData::PublicKeyAndSignature(_, signature) => signature,
-        };
-        buffer[sig_range_unpadded(tags_len, payload_len, 64)]
-            .copy_from_slice(sig.to_bytes().as_slice());
-
-        let record = Record::from_inner(&buffer[..len]);
-        if cfg!(debug_assertions) {
-            record.verify()?;
-        }
-
-        Ok(record)
+        Ok((
+            len,
+            sig_range_unpadded(tags_len, payload_len, sig_len),
+            hasher,
+        ))
     }
 
     /// Verify invariants. You should not normally need to call this; all code paths
@@ -205,6 +259,41 @@ impl Record {
     /// area is not zeroed.
     #[allow(clippy::missing_panics_doc)]
     pub fn verify(&self) -> Result<(), Error> {
+        let (signature, hasher) = self.verify_except_signature()?;
+        signature.verify_hasher(self.0[SIGNING_KEY_RANGE].try_into().unwrap(), hasher)
+    }
+
+    /// Verify the record as `verify()` does, and also that its signing key was
+    /// authorized to sign for its author at its timestamp according to the
+    /// author's `KeySchedule`.
+    ///
+    /// The author's master key is always authorized. Other keys are authorized
+    /// as described in `KeySchedule::authorizes()`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if verification fails, or
+    /// `InnerError::UnauthorizedSigningKey` if the signing key was not
+    /// authorized.
+    #[allow(clippy::missing_panics_doc)]
+    pub fn verify_authorized(&self, key_schedule: &KeySchedule) -> Result<(), Error> {
+        self.verify()?;
+
+        let signing_key: [u8; 32] = self.0[SIGNING_KEY_RANGE].try_into().unwrap();
+        let scheme = self.flags().get_signature_scheme();
+        if scheme == SignatureScheme::Ed25519 && signing_key == self.0[ADDR_AUTHOR_KEY_RANGE] {
+            return Ok(());
+        }
+        if key_schedule.authorizes(&signing_key, scheme, self.timestamp()) {
+            Ok(())
+        } else {
+            Err(InnerError::UnauthorizedSigningKey.into())
+        }
+    }
+
+    // Verify everything but the signature itself, returning the signature and
+    // the hasher over the signed data so the caller can verify it
+    fn verify_except_signature(&self) -> Result<(RecordSignature, Blake3), Error> {
         // Verify all lengths
         if self.0.len() > 1_048_576 {
             return Err(InnerError::RecordTooLong.into());
@@ -214,14 +303,29 @@ impl Record {
         }
         let tags_len = self.tag_set_padded_len();
         let payload_len = self.payload_padded_len();
-        let sig_len = 64;
-        if HEADER_LEN + tags_len + payload_len + sig_len != self.0.len() {
+        let sig_len = self.signature_len();
+        if HEADER_LEN + tags_len + payload_len + padded_len!(sig_len) != self.0.len() {
             return Err(InnerError::RecordSectionLengthMismatch.into());
         }
 
-        // Verify PublicKey validity
-        let signing_public_key =
-            PublicKey::from_bytes(self.0[SIGNING_KEY_RANGE].try_into().unwrap())?;
+        // Verify the signature padding is zeroed, as it is not covered by the
+        // hash or the signature
+        let sig_range = sig_range_unpadded(tags_len, payload_len, sig_len);
+        if self.0[sig_range.end..].iter().any(|b| *b != 0) {
+            return Err(InnerError::ReservedSpaceUsed.into());
+        }
+
+        // Verify the signature scheme is supported
+        let scheme = self.flags().get_signature_scheme();
+        if !matches!(
+            scheme,
+            SignatureScheme::Ed25519 | SignatureScheme::Secp256k1
+        ) {
+            return Err(InnerError::UnsupportedSignatureScheme(scheme).into());
+        }
+
+        // Verify PublicKey validity (the signing key is checked when the
+        // signature is verified, as it depends upon the scheme)
         let _author_public_key =
             PublicKey::from_bytes(self.0[ADDR_AUTHOR_KEY_RANGE].try_into().unwrap())?;
 
@@ -245,18 +349,14 @@ impl Record {
             return Err(InnerError::TimestampMismatch.into());
         }
 
-        // Verify the signature
-        let signature =
-            Signature::from_slice(&self.0[sig_range_unpadded(tags_len, payload_len, 64)])?;
-        signing_public_key.verify_signature_with_hasher(hasher, &signature)?;
-
         // Verify reserved flags are 0
-        let flags = self.flags();
-        if flags | RecordFlags::all() != RecordFlags::all() {
+        if self.flags().uses_reserved_bits() {
             return Err(InnerError::ReservedFlagsUsed.into());
         }
 
-        Ok(())
+        let signature = RecordSignature::from_bytes(scheme, &self.0[sig_range])?;
+
+        Ok((signature, hasher))
     }
 
     /// Signature length
@@ -266,23 +366,31 @@ impl Record {
         u16::from_le_bytes(self.0[LEN_S_RANGE].try_into().unwrap()) as usize
     }
 
-    /// Signature
+    /// Signature padded length
+    #[must_use]
+    pub fn signature_padded_len(&self) -> usize {
+        padded_len!(self.signature_len())
+    }
+
+    /// Signature area bytes (unpadded), of whatever length the record declares
+    #[must_use]
+    pub fn signature_bytes(&self) -> &[u8] {
+        &self.0[sig_range_unpadded(
+            self.tag_set_padded_len(),
+            self.payload_padded_len(),
+            self.signature_len(),
+        )]
+    }
+
+    /// Signature, in the record's `SignatureScheme`
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// Panics if the signature is not 64 bytes long, which is currently unsupported.
-    #[must_use]
-    pub fn signature(&self) -> Signature {
-        let sig_len = self.signature_len();
-        assert!(sig_len == 64, "Unsupported signature not 64 bytes long");
-        Signature::from_slice(
-            &self.0[sig_range_unpadded(
-                self.tag_set_padded_len(),
-                self.payload_padded_len(),
-                sig_len,
-            )],
-        )
-        .unwrap()
+    /// Returns an `Err` if the signature scheme is reserved, or if the signature
+    /// bytes are not a valid signature of the scheme (e.g. of the wrong length).
+    /// Neither happens on a verified record.
+    pub fn signature(&self) -> Result<RecordSignature, Error> {
+        RecordSignature::from_bytes(self.flags().get_signature_scheme(), self.signature_bytes())
     }
 
     /// The full 64-byte BLAKE3 hash of the contents `[112:]`
@@ -311,11 +419,27 @@ impl Record {
         unsafe { Address::from_bytes_unchecked(self.0[ADDR_RANGE].try_into().unwrap()) }
     }
 
-    /// Signing `PublicKey`
+    /// Signing `PublicKey`, if the record uses `SignatureScheme::Ed25519`
+    ///
+    /// Records using other schemes are signed by keys that are not
+    /// `PublicKey`s. Use `signing_key_bytes()` for those.
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn signing_public_key(&self) -> Option<PublicKey> {
+        if self.flags().get_signature_scheme() != SignatureScheme::Ed25519 {
+            return None;
+        }
+        Some(unsafe {
+            PublicKey::from_bytes_unchecked(self.0[SIGNING_KEY_RANGE].try_into().unwrap())
+        })
+    }
+
+    /// Signing key bytes, which are an ed25519 key or an x-only secp256k1
+    /// key depending on the `SignatureScheme`
     #[allow(clippy::missing_panics_doc)]
     #[must_use]
-    pub fn signing_public_key(&self) -> PublicKey {
-        unsafe { PublicKey::from_bytes_unchecked(self.0[SIGNING_KEY_RANGE].try_into().unwrap()) }
+    pub fn signing_key_bytes(&self) -> &[u8; 32] {
+        self.0[SIGNING_KEY_RANGE].try_into().unwrap()
     }
 
     /// Author `PublicKey`
@@ -393,15 +517,60 @@ impl Record {
 
     /// Payload area bytes
     ///
-    /// These are the raw bytes. If Zstd is used, the caller is responsible for
-    /// decompressing them.
+    /// These are the raw bytes. If Zstd is used, these are compressed; see
+    /// `payload()` for the decompressed payload.
     #[must_use]
     pub fn payload_bytes(&self) -> &[u8] {
         let start = HEADER_LEN + self.tag_set_padded_len();
         &self.0[start..start + self.payload_len()]
     }
+
+    /// Payload, decompressed if `RecordFlags::ZSTD` is set
+    ///
+    /// Decompressed payloads are limited to `MAX_DECOMPRESSED_PAYLOAD_LEN`
+    /// bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the payload is compressed and the `zstd` feature is
+    /// not enabled, if the compressed data is invalid, or if it decompresses
+    /// to more than the maximum length.
+    pub fn payload(&self) -> Result<Cow<'_, [u8]>, Error> {
+        self.payload_with_limit(MAX_DECOMPRESSED_PAYLOAD_LEN)
+    }
+
+    /// Payload, decompressed if `RecordFlags::ZSTD` is set, to at most
+    /// `max_len` bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the payload is compressed and the `zstd` feature is
+    /// not enabled, if the compressed data is invalid, or if it decompresses
+    /// to more than `max_len` bytes.
+    pub fn payload_with_limit(&self, max_len: usize) -> Result<Cow<'_, [u8]>, Error> {
+        if !self.flags().contains(RecordFlags::ZSTD) {
+            return Ok(Cow::Borrowed(self.payload_bytes()));
+        }
+
+        #[cfg(feature = "zstd")]
+        {
+            Ok(Cow::Owned(compression::decompress(
+                self.payload_bytes(),
+                max_len,
+            )?))
+        }
+
+        #[cfg(not(feature = "zstd"))]
+        {
+            let _ = max_len;
+            Err(InnerError::CompressionNotSupported.into())
+        }
+    }
 }
 
+/// The maximum length that `Record::payload()` will decompress a payload to
+pub const MAX_DECOMPRESSED_PAYLOAD_LEN: usize = 16_777_216;
+
 const ID_RANGE: Range<usize> = 0..48;
 const ID_TIMESTAMP_RANGE: Range<usize> = 0..8;
 const ID_HASH_RANGE: Range<usize> = 8..48;
@@ -442,6 +611,14 @@ fn full_len(tags_len: usize, payload_len: usize, sig_len: usize) -> usize {
     HEADER_LEN + padded_len!(tags_len) + padded_len!(payload_len) + padded_len!(sig_len)
 }
 
+// The full length of a record, as declared by its header
+fn full_len_from_header(header: &[u8]) -> usize {
+    let tags_len = u16::from_le_bytes(header[LEN_T_RANGE].try_into().unwrap()) as usize;
+    let payload_len = u32::from_le_bytes(header[LEN_P_RANGE].try_into().unwrap()) as usize;
+    let sig_len = u16::from_le_bytes(header[LEN_S_RANGE].try_into().unwrap()) as usize;
+    full_len(tags_len, payload_len, sig_len)
+}
+
 impl std::fmt::Display for Record {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "id: {}", self.id().as_printable())?;
@@ -454,22 +631,24 @@ impl std::fmt::Display for Record {
         writeln!(
             f,
             "  signing key: {}",
-            self.signing_public_key().as_printable()
+            PublicKey::bytes_as_printable(self.signing_key_bytes())
         )?;
         writeln!(f, "  timestamp: {}", self.timestamp())?;
         writeln!(f, "  kind: {}", self.kind())?;
         writeln!(f, "  flags: {}", self.flags())?;
-        writeln!(
-            f,
-            "  tag_set (zbase32): {}",
-            z32::encode(self.tag_set().as_bytes())
-        )?;
-        if self.kind().content_is_printable() {
-            writeln!(
-                f,
-                "  payload: {}",
-                String::from_utf8_lossy(self.payload_bytes())
-            )?;
+        writeln!(f, "  tags:")?;
+        for tag in self.tag_set() {
+            writeln!(f, "    {tag}")?;
+        }
+        let printable_payload = if self.kind().content_is_printable() {
+            self.payload().ok()
+        } else {
+            None
+        };
+        if let Some(formatted) = crate::kind_registry::format_payload(self) {
+            writeln!(f, "  payload: {formatted}")?;
+        } else if let Some(payload) = printable_payload {
+            writeln!(f, "  payload: {}", String::from_utf8_lossy(&payload))?;
         } else {
             writeln!(
                 f,
@@ -601,13 +780,112 @@ impl std::fmt::Display for OwnedRecord {
 }
 
 /// Record Signing data, used to assemble a Record
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub enum RecordSigningData {
     /// A `SecretKey`
     SecretKey(SecretKey),
 
     /// A `PublicKey` and the `Signature` of the record
     PublicKeyAndSignature(PublicKey, Signature),
+
+    /// A secp256k1 `SchnorrSigningKey` (e.g. a nostr key)
+    Secp256k1SecretKey(SchnorrSigningKey),
+
+    /// A secp256k1 `SchnorrVerifyingKey` and the `SchnorrSignature` of the record
+    Secp256k1PublicKeyAndSignature(SchnorrVerifyingKey, SchnorrSignature),
+
+    /// A `Signer`, which may hold its key outside of this process
+    Signer(Arc<dyn Signer>),
+
+    /// A `PublicKey` only, for a record signed by an `AsyncSigner` or
+    /// prepared as an `UnsignedRecord` that is signed later
+    PublicKey(PublicKey),
+
+    /// A secp256k1 `SchnorrVerifyingKey` only, for a record signed by an
+    /// `AsyncSigner` or prepared as an `UnsignedRecord` that is signed later
+    Secp256k1PublicKey(SchnorrVerifyingKey),
+}
+
+impl RecordSigningData {
+    /// The `SignatureScheme` of records signed with this data
+    #[must_use]
+    pub fn signature_scheme(&self) -> SignatureScheme {
+        match self {
+            RecordSigningData::SecretKey(_)
+            | RecordSigningData::PublicKeyAndSignature(..)
+            | RecordSigningData::PublicKey(_) => SignatureScheme::Ed25519,
+            RecordSigningData::Secp256k1SecretKey(_)
+            | RecordSigningData::Secp256k1PublicKeyAndSignature(..)
+            | RecordSigningData::Secp256k1PublicKey(_) => SignatureScheme::Secp256k1,
+            RecordSigningData::Signer(signer) => signer.signature_scheme(),
+        }
+    }
+
+    // The length of the signature that this signing data produces. A
+    // `Signer` may claim a reserved scheme, which cannot be written.
+    fn signature_len(&self) -> Result<usize, Error> {
+        match self.signature_scheme() {
+            SignatureScheme::Ed25519 | SignatureScheme::Secp256k1 => Ok(64),
+            scheme @ (SignatureScheme::Reserved2 | SignatureScheme::Reserved3) => {
+                Err(InnerError::UnsupportedSignatureScheme(scheme).into())
+            }
+        }
+    }
+
+    // The bytes of the signing public key, as stored in the record
+    fn signing_key_bytes(&self) -> [u8; 32] {
+        match self {
+            RecordSigningData::SecretKey(secret_key) => secret_key.public().to_bytes(),
+            RecordSigningData::PublicKeyAndSignature(public_key, _)
+            | RecordSigningData::PublicKey(public_key) => public_key.to_bytes(),
+            RecordSigningData::Secp256k1SecretKey(signing_key) => {
+                signing_key.verifying_key().to_bytes().into()
+            }
+            RecordSigningData::Secp256k1PublicKeyAndSignature(verifying_key, _)
+            | RecordSigningData::Secp256k1PublicKey(verifying_key) => {
+                verifying_key.to_bytes().into()
+            }
+            RecordSigningData::Signer(signer) => signer.signing_key_bytes(),
+        }
+    }
+}
+
+// Manual impl because `SchnorrSigningKey` and `Signer` do not implement Debug;
+// their public halves are shown instead
+impl std::fmt::Debug for RecordSigningData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecordSigningData::SecretKey(secret_key) => {
+                f.debug_tuple("SecretKey").field(secret_key).finish()
+            }
+            RecordSigningData::PublicKeyAndSignature(public_key, signature) => f
+                .debug_tuple("PublicKeyAndSignature")
+                .field(public_key)
+                .field(signature)
+                .finish(),
+            RecordSigningData::Secp256k1SecretKey(signing_key) => f
+                .debug_tuple("Secp256k1SecretKey")
+                .field(signing_key.verifying_key())
+                .finish(),
+            RecordSigningData::Secp256k1PublicKeyAndSignature(verifying_key, signature) => f
+                .debug_tuple("Secp256k1PublicKeyAndSignature")
+                .field(verifying_key)
+                .field(signature)
+                .finish(),
+            RecordSigningData::Signer(signer) => f
+                .debug_tuple("Signer")
+                .field(&signer.signature_scheme())
+                .field(&signer.signing_key_bytes())
+                .finish(),
+            RecordSigningData::PublicKey(public_key) => {
+                f.debug_tuple("PublicKey").field(public_key).finish()
+            }
+            RecordSigningData::Secp256k1PublicKey(verifying_key) => f
+                .debug_tuple("Secp256k1PublicKey")
+                .field(verifying_key)
+                .finish(),
+        }
+    }
 }
 
 /// Record Address data, used to assemble a Record
@@ -661,11 +939,14 @@ pub struct RecordParts<'a> {
 
 impl RecordParts<'_> {
     /// Compute the length of the record that would be created from these parts
+    ///
+    /// If the signature scheme is unsupported, no record can be created, and
+    /// this counts no signature.
     #[must_use]
     pub fn record_len(&self) -> usize {
         let padded_tag_set_len = padded_len!(self.tag_set.as_bytes().len());
         let padded_payload_len = padded_len!(self.payload.len());
-        let padded_sig_len = 64;
+        let padded_sig_len = padded_len!(self.signing_data.signature_len().unwrap_or(0));
         HEADER_LEN + padded_tag_set_len + padded_payload_len + padded_sig_len
     }
 }
@@ -718,4 +999,167 @@ mod test {
 
         assert!(r3 > r1);
     }
+
+    #[test]
+    fn test_secp256k1_record() {
+        let author_secret_key = SecretKey::generate();
+        let author_public_key = author_secret_key.public();
+        let signing_key = SchnorrSigningKey::random(&mut scrypt::password_hash::rand_core::OsRng);
+
+        let r = OwnedRecord::new(&RecordParts {
+            signing_data: RecordSigningData::Secp256k1SecretKey(signing_key.clone()),
+            address_data: RecordAddressData::Random(author_public_key, Kind::MICROBLOG_ROOT),
+            timestamp: Timestamp::from_unixtime(1_700_000_000, 0).unwrap(),
+            flags: RecordFlags::FROM_AUTHOR,
+            tag_set: &EMPTY_TAG_SET,
+            payload: b"hello from a nostr key",
+        })
+        .unwrap();
+
+        assert_eq!(r.flags().get_signature_scheme(), SignatureScheme::Secp256k1);
+        assert!(r.flags().contains(RecordFlags::FROM_AUTHOR));
+        assert_eq!(
+            r.signing_key_bytes().as_slice(),
+            signing_key.verifying_key().to_bytes().as_slice()
+        );
+        assert!(r.signing_public_key().is_none());
+        assert_eq!(r.signature().unwrap().scheme(), SignatureScheme::Secp256k1);
+
+        // Round trip through bytes
+        let r2 = Record::from_bytes(r.as_bytes()).unwrap();
+        assert_eq!(*r, *r2);
+
+        // Reassemble from the verifying key and signature
+        let RecordSignature::Secp256k1(signature) = r.signature().unwrap() else {
+            panic!("Wrong signature scheme");
+        };
+        let r3 = OwnedRecord::new(&RecordParts {
+            signing_data: RecordSigningData::Secp256k1PublicKeyAndSignature(
+                *signing_key.verifying_key(),
+                signature,
+            ),
+            address_data: RecordAddressData::Address(r.address()),
+            timestamp: r.timestamp(),
+            flags: RecordFlags::FROM_AUTHOR,
+            tag_set: &EMPTY_TAG_SET,
+            payload: b"hello from a nostr key",
+        })
+        .unwrap();
+        assert_eq!(r, r3);
+
+        // Tampering is detected
+        let mut bytes = r.as_bytes().to_owned();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        assert!(Record::from_bytes(&bytes).is_err());
+
+        // Reserved signature schemes are rejected
+        let mut bytes = r.as_bytes().to_owned();
+        bytes[136] |= 0b1100_0000;
+        assert!(matches!(
+            Record::from_bytes(&bytes).unwrap_err().inner,
+            InnerError::UnsupportedSignatureScheme(SignatureScheme::Reserved3)
+        ));
+    }
+
+    #[test]
+    fn test_signature_len() {
+        let secret_key = SecretKey::generate();
+        let r = RecordBuilder::new(Kind::EXAMPLE, secret_key)
+            .payload(b"hello world")
+            .unwrap()
+            .timestamp(Timestamp::from_unixtime(1_700_000_000, 0).unwrap())
+            .sign()
+            .unwrap();
+        assert_eq!(r.signature_len(), 64);
+        assert_eq!(r.signature_padded_len(), 64);
+        assert_eq!(r.signature_bytes(), r.signature().unwrap().to_bytes());
+
+        // Declare a longer signature, as a future scheme might
+        let mut bytes = r.as_bytes().to_owned();
+        bytes[146..148].copy_from_slice(70_u16.to_le_bytes().as_slice());
+        bytes.extend_from_slice(&[0; 8]);
+        let r2 = unsafe { Record::from_bytes_unchecked(&bytes).unwrap() };
+        assert_eq!(r2.as_bytes().len(), r.as_bytes().len() + 8);
+        assert_eq!(r2.signature_len(), 70);
+        assert_eq!(r2.signature_bytes().len(), 70);
+        assert!(r2.signature().is_err());
+        assert!(r2.verify().is_err());
+
+        // Truncated input is an error, not a panic
+        assert!(matches!(
+            Record::from_bytes(&bytes[..bytes.len() - 8])
+                .unwrap_err()
+                .inner,
+            InnerError::EndOfInput
+        ));
+
+        // Nonzero signature padding is rejected
+        let mut bytes = r.as_bytes().to_owned();
+        bytes[146..148].copy_from_slice(60_u16.to_le_bytes().as_slice());
+        assert!(matches!(
+            Record::from_bytes(&bytes).unwrap_err().inner,
+            InnerError::ReservedSpaceUsed
+        ));
+    }
+
+    #[test]
+    fn test_reserved_signer_scheme() {
+        // A `Signer` claiming a reserved scheme is refused, not a panic
+        struct ReservedSigner(SecretKey);
+        impl SignerIdentity for ReservedSigner {
+            fn signature_scheme(&self) -> SignatureScheme {
+                SignatureScheme::Reserved2
+            }
+            fn signing_key_bytes(&self) -> [u8; 32] {
+                self.0.public().to_bytes()
+            }
+        }
+        impl Signer for ReservedSigner {
+            fn sign_record_digest(&self, digest: &[u8; 64]) -> Result<RecordSignature, Error> {
+                self.0.sign_record_digest(digest)
+            }
+            fn sign_dht_item(&self, signable: &[u8]) -> Result<DalekSignature, Error> {
+                self.0.sign_dht_item(signable)
+            }
+        }
+
+        let secret_key = SecretKey::generate();
+        let signer = std::sync::Arc::new(ReservedSigner(secret_key.clone()));
+        let builder = RecordBuilder::new_with_signer(Kind::EXAMPLE, secret_key.public(), signer)
+            .payload(b"hello world")
+            .unwrap()
+            .timestamp(Timestamp::from_unixtime(1_700_000_000, 0).unwrap());
+        assert!(matches!(
+            builder.sign().unwrap_err().inner,
+            InnerError::UnsupportedSignatureScheme(SignatureScheme::Reserved2)
+        ));
+    }
+
+    #[test]
+    fn test_verify_authorized() {
+        let master = SecretKey::generate();
+        let subkey = SecretKey::generate();
+        let timestamp = Timestamp::from_unixtime(1_700_000_000, 0).unwrap();
+        let make = |signing_key: &SecretKey| {
+            RecordBuilder::new(Kind::EXAMPLE, signing_key.clone())
+                .author(master.public())
+                .timestamp(timestamp)
+                .sign()
+                .unwrap()
+        };
+
+        let key_schedule = KeySchedule::new(vec![]).unwrap();
+        make(&master).verify_authorized(&key_schedule).unwrap();
+        let e = make(&subkey).verify_authorized(&key_schedule).unwrap_err();
+        assert!(matches!(e.inner, InnerError::UnauthorizedSigningKey));
+
+        let key_schedule = KeySchedule::new(vec![KeyScheduleEntry {
+            public_key: subkey.public(),
+            marker: SubkeyMarker::ActiveSigningKey,
+            timestamp: Timestamp::ZERO,
+        }])
+        .unwrap();
+        make(&subkey).verify_authorized(&key_schedule).unwrap();
+    }
 }
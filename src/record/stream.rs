@@ -0,0 +1,426 @@
+use super::{full_len_from_header, HEADER_LEN};
+use crate::{Error, InnerError, OwnedRecord, Record};
+use futures::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use std::io::{ErrorKind, Read, Write};
+
+/// Reads a sequence of concatenated `Record`s from a `Read`
+///
+/// One buffer is reused for every record, and no more than `1_048_576` bytes
+/// are ever buffered. Records are verified as they are read.
+///
+/// As an `Iterator` it yields `OwnedRecord`s. Use `read_record()` to borrow
+/// each record from the buffer instead.
+#[derive(Debug)]
+pub struct RecordReader<R> {
+    inner: R,
+    buffer: Vec<u8>,
+    failed: bool,
+}
+
+impl<R: Read> RecordReader<R> {
+    /// Create a new `RecordReader`
+    #[must_use]
+    pub fn new(inner: R) -> RecordReader<R> {
+        RecordReader {
+            inner,
+            buffer: Vec::with_capacity(HEADER_LEN),
+            failed: false,
+        }
+    }
+
+    /// Read the next `Record`, or `None` if the stream has ended cleanly
+    /// between records.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` on I/O errors, if the stream ends partway through a
+    /// record, if the record is too long, or if it fails verification. After a
+    /// verification error the reader is positioned at the next record. After
+    /// any other error the reader can no longer find the next record, and
+    /// returns `None` from then on.
+    pub fn read_record(&mut self) -> Result<Option<&Record>, Error> {
+        if self.failed {
+            return Ok(None);
+        }
+        match self.read_frame() {
+            Ok(true) => Ok(Some(Record::from_bytes(&self.buffer)?)),
+            Ok(false) => Ok(None),
+            Err(e) => {
+                self.failed = true;
+                Err(e)
+            }
+        }
+    }
+
+    // Read the next record's bytes into the buffer, returning false if the
+    // stream has ended cleanly
+    fn read_frame(&mut self) -> Result<bool, Error> {
+        self.buffer.resize(HEADER_LEN, 0);
+        if !fill_or_eof(&mut self.inner, &mut self.buffer)? {
+            return Ok(false);
+        }
+
+        let len = record_len(&self.buffer)?;
+        self.buffer.resize(len, 0);
+        self.inner
+            .read_exact(&mut self.buffer[HEADER_LEN..])
+            .map_err(truncated)?;
+        Ok(true)
+    }
+
+    /// Get a reference to the underlying reader
+    #[must_use]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Unwrap the underlying reader
+    #[must_use]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Iterator for RecordReader<R> {
+    type Item = Result<OwnedRecord, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record()
+            .map(|r| r.map(Record::to_owned))
+            .transpose()
+    }
+}
+
+/// Reads a sequence of concatenated `Record`s from an `AsyncRead`
+///
+/// This is the async equivalent of `RecordReader`.
+#[derive(Debug)]
+pub struct AsyncRecordReader<R> {
+    inner: R,
+    buffer: Vec<u8>,
+    failed: bool,
+}
+
+impl<R: AsyncRead + Unpin> AsyncRecordReader<R> {
+    /// Create a new `AsyncRecordReader`
+    #[must_use]
+    pub fn new(inner: R) -> AsyncRecordReader<R> {
+        AsyncRecordReader {
+            inner,
+            buffer: Vec::with_capacity(HEADER_LEN),
+            failed: false,
+        }
+    }
+
+    /// Read the next `Record`, or `None` if the stream has ended cleanly
+    /// between records.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` on I/O errors, if the stream ends partway through a
+    /// record, if the record is too long, or if it fails verification. After a
+    /// verification error the reader is positioned at the next record. After
+    /// any other error the reader can no longer find the next record, and
+    /// returns `None` from then on.
+    pub async fn read_record(&mut self) -> Result<Option<&Record>, Error> {
+        if self.failed {
+            return Ok(None);
+        }
+        match self.read_frame().await {
+            Ok(true) => Ok(Some(Record::from_bytes(&self.buffer)?)),
+            Ok(false) => Ok(None),
+            Err(e) => {
+                self.failed = true;
+                Err(e)
+            }
+        }
+    }
+
+    // Read the next record's bytes into the buffer, returning false if the
+    // stream has ended cleanly
+    async fn read_frame(&mut self) -> Result<bool, Error> {
+        self.buffer.resize(HEADER_LEN, 0);
+        if !fill_or_eof_async(&mut self.inner, &mut self.buffer).await? {
+            return Ok(false);
+        }
+
+        let len = record_len(&self.buffer)?;
+        self.buffer.resize(len, 0);
+        self.inner
+            .read_exact(&mut self.buffer[HEADER_LEN..])
+            .await
+            .map_err(truncated)?;
+        Ok(true)
+    }
+
+    /// Read the next `Record` into an `OwnedRecord`, or `None` if the stream
+    /// has ended cleanly between records.
+    ///
+    /// # Errors
+    ///
+    /// See `read_record()`
+    pub async fn read_owned_record(&mut self) -> Result<Option<OwnedRecord>, Error> {
+        Ok(self.read_record().await?.map(Record::to_owned))
+    }
+
+    /// Get a reference to the underlying reader
+    #[must_use]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Unwrap the underlying reader
+    #[must_use]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+/// Writes a sequence of concatenated `Record`s to a `Write`, readable with
+/// a `RecordReader`
+#[derive(Debug)]
+pub struct RecordWriter<W> {
+    inner: W,
+}
+
+impl<W: Write> RecordWriter<W> {
+    /// Create a new `RecordWriter`
+    #[must_use]
+    pub fn new(inner: W) -> RecordWriter<W> {
+        RecordWriter { inner }
+    }
+
+    /// Write a `Record`
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` on I/O errors
+    pub fn write_record(&mut self, record: &Record) -> Result<(), Error> {
+        self.inner.write_all(record.as_bytes())?;
+        Ok(())
+    }
+
+    /// Flush the underlying writer
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` on I/O errors
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.inner.flush()?;
+        Ok(())
+    }
+
+    /// Get a reference to the underlying writer
+    #[must_use]
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Unwrap the underlying writer
+    #[must_use]
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// Writes a sequence of concatenated `Record`s to an `AsyncWrite`, readable
+/// with an `AsyncRecordReader`
+#[derive(Debug)]
+pub struct AsyncRecordWriter<W> {
+    inner: W,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncRecordWriter<W> {
+    /// Create a new `AsyncRecordWriter`
+    #[must_use]
+    pub fn new(inner: W) -> AsyncRecordWriter<W> {
+        AsyncRecordWriter { inner }
+    }
+
+    /// Write a `Record`
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` on I/O errors
+    pub async fn write_record(&mut self, record: &Record) -> Result<(), Error> {
+        self.inner.write_all(record.as_bytes()).await?;
+        Ok(())
+    }
+
+    /// Flush the underlying writer
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` on I/O errors
+    pub async fn flush(&mut self) -> Result<(), Error> {
+        self.inner.flush().await?;
+        Ok(())
+    }
+
+    /// Get a reference to the underlying writer
+    #[must_use]
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Unwrap the underlying writer
+    #[must_use]
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+// The length of the record whose header is given, bounded by the maximum
+// record length so that a bad header cannot make us buffer more
+fn record_len(header: &[u8]) -> Result<usize, Error> {
+    let len = full_len_from_header(header);
+    if len > 1_048_576 {
+        return Err(InnerError::RecordTooLong.into());
+    }
+    Ok(len)
+}
+
+fn truncated(e: std::io::Error) -> Error {
+    if e.kind() == ErrorKind::UnexpectedEof {
+        InnerError::TruncatedRecord.into()
+    } else {
+        e.into()
+    }
+}
+
+// Fill the buffer, returning false if the stream ended before any bytes were
+// read, and an error if it ended partway
+fn fill_or_eof<R: Read>(reader: &mut R, buffer: &mut [u8]) -> Result<bool, Error> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) if filled == 0 => return Ok(false),
+            Ok(0) => return Err(InnerError::TruncatedRecord.into()),
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(true)
+}
+
+async fn fill_or_eof_async<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    buffer: &mut [u8],
+) -> Result<bool, Error> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]).await {
+            Ok(0) if filled == 0 => return Ok(false),
+            Ok(0) => return Err(InnerError::TruncatedRecord.into()),
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    fn make_records() -> Vec<OwnedRecord> {
+        let secret_key = SecretKey::generate();
+        let timestamp = Timestamp::from_unixtime(1_700_000_000, 0).unwrap();
+        (0..5)
+            .map(|i| {
+                RecordBuilder::new(Kind::CHAT_MESSAGE, secret_key.clone())
+                    .payload("x".repeat(i * 100).as_bytes())
+                    .unwrap()
+                    .timestamp(timestamp)
+                    .sign()
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_record_reader_writer() {
+        let records = make_records();
+
+        let mut writer = RecordWriter::new(Vec::new());
+        for record in &records {
+            writer.write_record(record).unwrap();
+        }
+        writer.flush().unwrap();
+        let bytes = writer.into_inner();
+
+        let reader = RecordReader::new(bytes.as_slice());
+        let read: Vec<OwnedRecord> = reader.collect::<Result<_, _>>().unwrap();
+        assert_eq!(read, records);
+
+        // Truncated in the middle of a record
+        let mut reader = RecordReader::new(&bytes[..bytes.len() - 10]);
+        for _ in 0..4 {
+            assert!(reader.read_record().unwrap().is_some());
+        }
+        let e = reader.read_record().unwrap_err();
+        assert!(matches!(e.inner, InnerError::TruncatedRecord));
+        assert!(reader.read_record().unwrap().is_none());
+
+        // Truncated in the middle of a header
+        let mut reader = RecordReader::new(&bytes[..100]);
+        let e = reader.read_record().unwrap_err();
+        assert!(matches!(e.inner, InnerError::TruncatedRecord));
+
+        // A verification error leaves the reader at the next record
+        let first_len = records[0].as_bytes().len();
+        let mut corrupted = bytes.clone();
+        corrupted[first_len - 1] ^= 1;
+        let results: Vec<Result<OwnedRecord, Error>> =
+            RecordReader::new(corrupted.as_slice()).collect();
+        assert_eq!(results.len(), 5);
+        assert!(results[0].is_err());
+        assert!(results[1..].iter().all(Result::is_ok));
+
+        // A framing error ends the iteration
+        let mut too_long = bytes.clone();
+        too_long[first_len + 148..first_len + 152].copy_from_slice(&[0xff; 4]);
+        let results: Vec<Result<OwnedRecord, Error>> =
+            RecordReader::new(too_long.as_slice()).collect();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_ok());
+        assert!(matches!(
+            results[1].as_ref().unwrap_err().inner,
+            InnerError::RecordTooLong
+        ));
+
+        // Empty
+        assert!(RecordReader::new(&[][..]).read_record().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_async_record_reader_writer() {
+        let records = make_records();
+
+        futures::executor::block_on(async {
+            let mut writer = AsyncRecordWriter::new(futures::io::Cursor::new(Vec::new()));
+            for record in &records {
+                writer.write_record(record).await.unwrap();
+            }
+            writer.flush().await.unwrap();
+            let bytes = writer.into_inner().into_inner();
+
+            let mut reader = AsyncRecordReader::new(bytes.as_slice());
+            let mut read: Vec<OwnedRecord> = Vec::new();
+            while let Some(record) = reader.read_owned_record().await.unwrap() {
+                read.push(record);
+            }
+            assert_eq!(read, records);
+
+            let mut reader = AsyncRecordReader::new(&bytes[..bytes.len() - 1]);
+            for _ in 0..4 {
+                assert!(reader.read_record().await.unwrap().is_some());
+            }
+            let e = reader.read_record().await.unwrap_err();
+            assert!(matches!(e.inner, InnerError::TruncatedRecord));
+            assert!(reader.read_record().await.unwrap().is_none());
+        });
+    }
+}
@@ -0,0 +1,187 @@
+use super::{OwnedRecord, FLAGS_RANGE, ID_RANGE, SIGNING_KEY_RANGE};
+use crate::{
+    AsyncSigner, Error, Id, InnerError, Record, RecordFlags, RecordParts, RecordSignature,
+    SignatureScheme,
+};
+use std::ops::Range;
+
+/// A record that is fully laid out but not yet signed.
+///
+/// This allows signing to happen elsewhere, such as on an offline or
+/// air-gapped machine: hand over the `digest()`, and `finalize()` with the
+/// signature that comes back.
+///
+/// ```
+/// # use mosaic_core::*;
+/// let secret_key = SecretKey::generate();
+/// let unsigned = RecordBuilder::with_signing_data(
+///     Kind::EXAMPLE,
+///     secret_key.public(),
+///     RecordSigningData::PublicKey(secret_key.public()),
+/// )
+/// .timestamp(Timestamp::from_unixtime(1_700_000_000, 0).unwrap())
+/// .prepare()
+/// .unwrap();
+///
+/// // ... elsewhere, the digest is signed ...
+/// let signature = secret_key.sign_record_digest(unsigned.digest()).unwrap();
+///
+/// let record = unsigned.finalize(signature).unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsignedRecord {
+    bytes: Vec<u8>,
+    sig_range: Range<usize>,
+    digest: [u8; 64],
+}
+
+impl UnsignedRecord {
+    /// Lay out a record from the `parts` without signing it.
+    ///
+    /// The signing data supplies only the signature scheme and signing key;
+    /// any key or signature it holds is not used. Use
+    /// `RecordSigningData::PublicKey` (or `Secp256k1PublicKey`) when the key is
+    /// not at hand.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if any data is too long, or if reserved flags are set.
+    pub fn new(parts: &RecordParts) -> Result<UnsignedRecord, Error> {
+        let mut bytes = vec![0; parts.record_len()];
+        let (len, sig_range, hasher) = Record::write_unsigned(&mut bytes, parts)?;
+        bytes.truncate(len);
+        Ok(UnsignedRecord {
+            bytes,
+            sig_range,
+            digest: digest::FixedOutput::finalize_fixed(hasher).into(),
+        })
+    }
+
+    /// The 64-byte BLAKE3 digest that must be signed.
+    ///
+    /// This is exactly what signing a record in-process signs; see
+    /// `Signer::sign_record_digest()`.
+    #[must_use]
+    pub fn digest(&self) -> &[u8; 64] {
+        &self.digest
+    }
+
+    /// The `Id` the record will have. It does not depend on the signature.
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn id(&self) -> Id {
+        unsafe { Id::from_bytes_unchecked(self.bytes[ID_RANGE].try_into().unwrap()) }
+    }
+
+    /// The `SignatureScheme` the record must be signed under
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn signature_scheme(&self) -> SignatureScheme {
+        let flags = u64::from_le_bytes(self.bytes[FLAGS_RANGE].try_into().unwrap());
+        RecordFlags::from_bits_retain(flags).get_signature_scheme()
+    }
+
+    /// The bytes of the public key that must make the signature
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn signing_key_bytes(&self) -> [u8; 32] {
+        self.bytes[SIGNING_KEY_RANGE].try_into().unwrap()
+    }
+
+    /// The laid out record, with the signature zeroed
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Attach the signature, producing the `OwnedRecord`
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the signature is of the wrong scheme, or is not a
+    /// valid signature of the digest by the signing key.
+    pub fn finalize(mut self, signature: RecordSignature) -> Result<OwnedRecord, Error> {
+        if signature.scheme() != self.signature_scheme() {
+            return Err(InnerError::SignerSchemeMismatch.into());
+        }
+        signature.verify_digest(&self.signing_key_bytes(), &self.digest)?;
+
+        self.bytes[self.sig_range].copy_from_slice(signature.to_bytes().as_slice());
+        let record = OwnedRecord(self.bytes);
+        if cfg!(debug_assertions) {
+            record.verify()?;
+        }
+        Ok(record)
+    }
+
+    /// Sign with an `AsyncSigner` and attach the signature
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if signing fails, or if the signer's signature does not
+    /// verify (e.g. because it holds a different key).
+    pub async fn sign<S: AsyncSigner + ?Sized>(self, signer: &S) -> Result<OwnedRecord, Error> {
+        let signature = signer.sign_record_digest_async(&self.digest).await?;
+        self.finalize(signature)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn test_unsigned_record() {
+        let secret_key = SecretKey::generate();
+        let timestamp = Timestamp::from_unixtime(1_700_000_000, 0).unwrap();
+        let builder = RecordBuilder::new(Kind::EXAMPLE, secret_key.clone())
+            .payload(b"Hello World!")
+            .unwrap()
+            .timestamp(timestamp)
+            .deterministic_address(b"hello");
+
+        let unsigned = builder.clone().prepare().unwrap();
+        assert_eq!(unsigned.signature_scheme(), SignatureScheme::Ed25519);
+        assert_eq!(unsigned.signing_key_bytes(), secret_key.public().to_bytes());
+
+        // The digest is what in-process signing signs, so signing it gives
+        // the same record (ed25519 signing is deterministic)
+        let signature = secret_key.sign_record_digest(unsigned.digest()).unwrap();
+        let record = unsigned.clone().finalize(signature).unwrap();
+        assert_eq!(record, builder.sign().unwrap());
+        assert_eq!(record.id(), unsigned.id());
+
+        // Wrong signatures are refused
+        let other = SecretKey::generate();
+        let signature = other.sign_record_digest(unsigned.digest()).unwrap();
+        assert!(unsigned.clone().finalize(signature).is_err());
+
+        let schnorr_key = SchnorrSigningKey::random(&mut scrypt::password_hash::rand_core::OsRng);
+        let signature = schnorr_key.sign_record_digest(unsigned.digest()).unwrap();
+        let e = unsigned.finalize(signature).unwrap_err();
+        assert!(matches!(e.inner, InnerError::SignerSchemeMismatch));
+    }
+
+    #[test]
+    fn test_unsigned_record_async() {
+        let schnorr_key = SchnorrSigningKey::random(&mut scrypt::password_hash::rand_core::OsRng);
+        let author = SecretKey::generate().public();
+
+        let unsigned = RecordBuilder::with_signing_data(
+            Kind::EXAMPLE,
+            author,
+            RecordSigningData::Secp256k1PublicKey(*schnorr_key.verifying_key()),
+        )
+        .timestamp(Timestamp::from_unixtime(1_700_000_000, 0).unwrap())
+        .prepare()
+        .unwrap();
+
+        let record = futures::executor::block_on(unsigned.sign(&schnorr_key)).unwrap();
+        record.verify().unwrap();
+        assert_eq!(record.author_public_key(), author);
+        assert_eq!(
+            record.flags().get_signature_scheme(),
+            SignatureScheme::Secp256k1
+        );
+    }
+}
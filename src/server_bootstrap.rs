@@ -1,6 +1,7 @@
-use crate::{Error, InnerError, PublicKey, SecretKey, Url};
+use crate::signer::sign_mutable_item;
+use crate::{AsyncSigner, Error, InnerError, PublicKey, Url};
 use mainline::async_dht::AsyncDht;
-use mainline::{Id, MutableItem};
+use mainline::Id;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
@@ -134,16 +135,16 @@ impl ServerBootstrap {
         }
     }
 
-    /// Try to write a `ServerBootstrap` record for the given `PublicKey`
-    /// using the supplied `Dht` state object.
+    /// Try to write a `ServerBootstrap` record for the `PublicKey` of the signer
+    /// (such as a `SecretKey`) using the supplied `Dht` state object.
     /// A Kademlia node Id is returned on success.
     ///
     /// # Errors
     ///
-    /// Returns an `Err` if it couldn't write to the Dht
-    pub async fn write_to_dht(
+    /// Returns an `Err` if signing failed or it couldn't write to the Dht
+    pub async fn write_to_dht<S: AsyncSigner + ?Sized>(
         &mut self,
-        secret_key: SecretKey,
+        signer: &S,
         dht: &AsyncDht,
     ) -> Result<Id, Error> {
         let s = self.to_dht_string();
@@ -156,12 +157,8 @@ impl ServerBootstrap {
         };
         self.1 += 1; // bump the sequence number
 
-        let mutable_item = MutableItem::new(
-            secret_key.to_signing_key(),
-            s.as_bytes(),
-            self.1,
-            Some(DHT_SERVER_SALT),
-        );
+        let mutable_item =
+            sign_mutable_item(signer, s.as_bytes(), self.1, Some(DHT_SERVER_SALT)).await?;
 
         let id = dht
             .put_mutable(mutable_item, cas)
@@ -223,7 +220,7 @@ mod test {
             // It has expired from the DHT
             // Let's write it
             let id = expected_server_bootstrap
-                .write_to_dht(secret_key, &async_dht)
+                .write_to_dht(&secret_key, &async_dht)
                 .await
                 .unwrap();
             println!("Stored new at {id}");
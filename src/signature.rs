@@ -1,5 +1,6 @@
-use crate::{Blake3, DalekSignature, DalekSigningKey, DalekVerifyingKey};
-use crate::{Error, InnerError};
+use crate::SignatureScheme;
+use crate::{Blake3, DalekSignature, DalekSigningKey, DalekVerifyingKey, Prehashed};
+use crate::{Error, InnerError, SchnorrSignature, SchnorrSigningKey, SchnorrVerifyingKey};
 use rand::RngCore;
 #[cfg(feature = "serde")]
 use serde::{de::Visitor, Deserialize, Deserializer, Serialize, Serializer};
@@ -64,7 +65,13 @@ impl PublicKey {
     /// Convert a `PublicKey` into the human printable `mopub0` form.
     #[must_use]
     pub fn as_printable(&self) -> String {
-        format!("mopub0{}", z32::encode(&self.0))
+        Self::bytes_as_printable(&self.0)
+    }
+
+    // Encode key bytes in the printable form, whether or not they are a
+    // valid ed25519 key (they may be a secp256k1 signing key)
+    pub(crate) fn bytes_as_printable(bytes: &[u8; 32]) -> String {
+        format!("mopub0{}", z32::encode(bytes))
     }
 
     /// Import a `PublicKey` from its printable form
@@ -73,14 +80,19 @@ impl PublicKey {
     ///
     /// Will return `Err` if the input is not a `PublicKey`
     pub fn from_printable(s: &str) -> Result<PublicKey, Error> {
+        Self::from_bytes(&Self::bytes_from_printable(s)?)
+    }
+
+    // Decode the key bytes of the printable form without checking that they
+    // are a valid ed25519 key (they may be a secp256k1 signing key)
+    pub(crate) fn bytes_from_printable(s: &str) -> Result<[u8; 32], Error> {
         if !s.starts_with("mopub0") {
             return Err(InnerError::InvalidPrintable.into_err());
         }
         let bytes = z32::decode(&s.as_bytes()[6..])?;
-        let bytes: [u8; 32] = bytes
+        bytes
             .try_into()
-            .map_err(|_| InnerError::KeyLength.into_err())?;
-        Self::from_bytes(&bytes)
+            .map_err(|_| InnerError::KeyLength.into_err())
     }
 
     /// Verify a signature on some data
@@ -253,6 +265,14 @@ impl SecretKey {
             .to_signing_key()
             .sign_prehashed(hasher, Some(b"Mosaic"))?)
     }
+
+    // Sign a 64-byte digest previously computed by a `Blake3`, exactly as
+    // `sign_hasher()` would have signed it
+    pub(crate) fn sign_digest(&self, digest: &[u8; 64]) -> Result<DalekSignature, Error> {
+        Ok(self
+            .to_signing_key()
+            .sign_prehashed(Prehashed(*digest), Some(b"Mosaic"))?)
+    }
 }
 
 impl std::fmt::Display for SecretKey {
@@ -269,6 +289,128 @@ impl PartialEq for SecretKey {
 
 impl Eq for SecretKey {}
 
+/// The signature on a `Record`, in any supported `SignatureScheme`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordSignature {
+    /// An ed25519 signature (Ed25519ph over the BLAKE3 hash, with the
+    /// 'Mosaic' context)
+    Ed25519(DalekSignature),
+
+    /// A BIP-340 secp256k1 schnorr signature over the 64-byte BLAKE3 hash
+    Secp256k1(SchnorrSignature),
+}
+
+impl RecordSignature {
+    /// The `SignatureScheme` of this signature
+    #[must_use]
+    pub fn scheme(&self) -> SignatureScheme {
+        match self {
+            RecordSignature::Ed25519(_) => SignatureScheme::Ed25519,
+            RecordSignature::Secp256k1(_) => SignatureScheme::Secp256k1,
+        }
+    }
+
+    /// Take bytes as `[u8; 64]`
+    #[must_use]
+    pub fn to_bytes(&self) -> [u8; 64] {
+        match self {
+            RecordSignature::Ed25519(sig) => sig.to_bytes(),
+            RecordSignature::Secp256k1(sig) => sig.to_bytes(),
+        }
+    }
+
+    /// Interpret bytes as a `RecordSignature` of the given `SignatureScheme`
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the scheme is reserved, or if the bytes are not a
+    /// valid signature of that scheme.
+    pub fn from_bytes(scheme: SignatureScheme, bytes: &[u8]) -> Result<RecordSignature, Error> {
+        match scheme {
+            SignatureScheme::Ed25519 => {
+                Ok(RecordSignature::Ed25519(DalekSignature::from_slice(bytes)?))
+            }
+            SignatureScheme::Secp256k1 => Ok(RecordSignature::Secp256k1(
+                SchnorrSignature::try_from(bytes)
+                    .map_err(|e| InnerError::Secp256k1(e).into_err())?,
+            )),
+            _ => Err(InnerError::UnsupportedSignatureScheme(scheme).into()),
+        }
+    }
+
+    // Verify this signature against the record hash being computed in `hasher`,
+    // as signed by the signing key with the given bytes
+    pub(crate) fn verify_hasher(
+        &self,
+        signing_key: &[u8; 32],
+        hasher: Blake3,
+    ) -> Result<(), Error> {
+        self.verify_digest(
+            signing_key,
+            &digest::FixedOutput::finalize_fixed(hasher).into(),
+        )
+    }
+
+    // Verify this signature against the 64-byte record digest, as signed by
+    // the signing key with the given bytes
+    pub(crate) fn verify_digest(
+        &self,
+        signing_key: &[u8; 32],
+        digest: &[u8; 64],
+    ) -> Result<(), Error> {
+        match self {
+            RecordSignature::Ed25519(sig) => Ok(PublicKey::from_bytes(signing_key)?
+                .to_verifying_key()
+                .verify_prehashed_strict(Prehashed(*digest), Some(b"Mosaic"), sig)?),
+            RecordSignature::Secp256k1(sig) => {
+                let verifying_key = SchnorrVerifyingKey::from_bytes(signing_key)
+                    .map_err(|e| InnerError::Secp256k1(e).into_err())?;
+                verifying_key
+                    .verify_raw(digest, sig)
+                    .map_err(|e| InnerError::Secp256k1(e).into())
+            }
+        }
+    }
+}
+
+impl From<DalekSignature> for RecordSignature {
+    fn from(sig: DalekSignature) -> RecordSignature {
+        RecordSignature::Ed25519(sig)
+    }
+}
+
+impl From<SchnorrSignature> for RecordSignature {
+    fn from(sig: SchnorrSignature) -> RecordSignature {
+        RecordSignature::Secp256k1(sig)
+    }
+}
+
+// Sign the record hash being computed in `hasher` with a secp256k1 key
+pub(crate) fn schnorr_sign_hasher(
+    signing_key: &SchnorrSigningKey,
+    hasher: Blake3,
+) -> Result<SchnorrSignature, Error> {
+    if hasher.is_empty() {
+        return Err(InnerError::DataTooShort.into());
+    }
+    schnorr_sign_digest(
+        signing_key,
+        &digest::FixedOutput::finalize_fixed(hasher).into(),
+    )
+}
+
+// Sign a 64-byte record digest with a secp256k1 key
+pub(crate) fn schnorr_sign_digest(
+    signing_key: &SchnorrSigningKey,
+    digest: &[u8; 64],
+) -> Result<SchnorrSignature, Error> {
+    let mut aux_rand = [0_u8; 32];
+    rand::rng().fill_bytes(&mut aux_rand);
+    signing_key
+        .sign_raw(digest, &aux_rand)
+        .map_err(|e| InnerError::Secp256k1(e).into())
+}
+
 /// An encrypted secret signing key
 /// whether a master key or subkey.
 //
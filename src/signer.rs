@@ -0,0 +1,368 @@
+use crate::{
+    DalekSignature, Error, InnerError, PublicKey, RecordSignature, SchnorrSigningKey, SecretKey,
+    SignatureScheme,
+};
+use futures::future::BoxFuture;
+use mainline::MutableItem;
+use std::sync::{Arc, Mutex};
+
+/// The public half of a `Signer` or `AsyncSigner`: which key signs, and
+/// under which `SignatureScheme`
+pub trait SignerIdentity {
+    /// The `SignatureScheme` of the signatures this signer makes
+    fn signature_scheme(&self) -> SignatureScheme;
+
+    /// The bytes of the public key that verifies this signer's signatures, as
+    /// stored in the signing key field of a record
+    fn signing_key_bytes(&self) -> [u8; 32];
+}
+
+/// Something that can sign records and DHT items on behalf of a key.
+///
+/// The key itself need not be in this process: an implementation may pass
+/// requests to a key agent, hardware device or another process. Records are
+/// signed over their 64-byte BLAKE3 digest, so the record itself never needs
+/// to be handed over.
+///
+/// `SecretKey` (ed25519) and `SchnorrSigningKey` (secp256k1) implement this
+/// in-process. Every `Signer` is also an `AsyncSigner`.
+pub trait Signer: SignerIdentity + Send + Sync {
+    /// Sign the 64-byte BLAKE3 digest of a record.
+    ///
+    /// For ed25519 this is an Ed25519ph signature with the 'Mosaic' context
+    /// and the digest as the prehash. For secp256k1 it is a BIP-340 signature
+    /// over the digest.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if signing fails or is refused
+    fn sign_record_digest(&self, digest: &[u8; 64]) -> Result<RecordSignature, Error>;
+
+    /// Sign a Mainline DHT mutable item (the bencoded salt, seq and value) with
+    /// a plain ed25519 signature.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if signing fails or is refused, or if the signer does
+    /// not hold an ed25519 key.
+    fn sign_dht_item(&self, signable: &[u8]) -> Result<DalekSignature, Error>;
+}
+
+/// An asynchronous `Signer`, for keys that are reached over some transport.
+///
+/// This is blanket implemented for every `Signer`.
+pub trait AsyncSigner: SignerIdentity + Send + Sync {
+    /// Sign the 64-byte BLAKE3 digest of a record. See
+    /// `Signer::sign_record_digest()`.
+    fn sign_record_digest_async<'a>(
+        &'a self,
+        digest: &'a [u8; 64],
+    ) -> BoxFuture<'a, Result<RecordSignature, Error>>;
+
+    /// Sign a Mainline DHT mutable item. See `Signer::sign_dht_item()`.
+    fn sign_dht_item_async<'a>(
+        &'a self,
+        signable: &'a [u8],
+    ) -> BoxFuture<'a, Result<DalekSignature, Error>>;
+}
+
+impl<S: Signer> AsyncSigner for S {
+    fn sign_record_digest_async<'a>(
+        &'a self,
+        digest: &'a [u8; 64],
+    ) -> BoxFuture<'a, Result<RecordSignature, Error>> {
+        Box::pin(async move { self.sign_record_digest(digest) })
+    }
+
+    fn sign_dht_item_async<'a>(
+        &'a self,
+        signable: &'a [u8],
+    ) -> BoxFuture<'a, Result<DalekSignature, Error>> {
+        Box::pin(async move { self.sign_dht_item(signable) })
+    }
+}
+
+impl<S: SignerIdentity + ?Sized> SignerIdentity for Arc<S> {
+    fn signature_scheme(&self) -> SignatureScheme {
+        (**self).signature_scheme()
+    }
+
+    fn signing_key_bytes(&self) -> [u8; 32] {
+        (**self).signing_key_bytes()
+    }
+}
+
+impl<S: Signer + ?Sized> Signer for Arc<S> {
+    fn sign_record_digest(&self, digest: &[u8; 64]) -> Result<RecordSignature, Error> {
+        (**self).sign_record_digest(digest)
+    }
+
+    fn sign_dht_item(&self, signable: &[u8]) -> Result<DalekSignature, Error> {
+        (**self).sign_dht_item(signable)
+    }
+}
+
+impl SignerIdentity for SecretKey {
+    fn signature_scheme(&self) -> SignatureScheme {
+        SignatureScheme::Ed25519
+    }
+
+    fn signing_key_bytes(&self) -> [u8; 32] {
+        self.public().to_bytes()
+    }
+}
+
+impl Signer for SecretKey {
+    fn sign_record_digest(&self, digest: &[u8; 64]) -> Result<RecordSignature, Error> {
+        Ok(self.sign_digest(digest)?.into())
+    }
+
+    fn sign_dht_item(&self, signable: &[u8]) -> Result<DalekSignature, Error> {
+        use ed25519_dalek::Signer as _;
+        Ok(self.to_signing_key().sign(signable))
+    }
+}
+
+impl SignerIdentity for SchnorrSigningKey {
+    fn signature_scheme(&self) -> SignatureScheme {
+        SignatureScheme::Secp256k1
+    }
+
+    fn signing_key_bytes(&self) -> [u8; 32] {
+        self.verifying_key().to_bytes().into()
+    }
+}
+
+impl Signer for SchnorrSigningKey {
+    fn sign_record_digest(&self, digest: &[u8; 64]) -> Result<RecordSignature, Error> {
+        Ok(crate::signature::schnorr_sign_digest(self, digest)?.into())
+    }
+
+    fn sign_dht_item(&self, _signable: &[u8]) -> Result<DalekSignature, Error> {
+        Err(InnerError::UnsupportedSignatureScheme(SignatureScheme::Secp256k1).into())
+    }
+}
+
+/// A request made of a `MockSigner`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SigningRequest {
+    /// A record digest was signed
+    RecordDigest([u8; 64]),
+
+    /// A DHT item was signed
+    DhtItem(Vec<u8>),
+}
+
+/// A `Signer` for tests, which signs with an in-process `SecretKey` and
+/// records every request made of it.
+///
+/// It can also be told to refuse requests, to test how signing failures are
+/// handled.
+#[derive(Debug)]
+pub struct MockSigner {
+    secret_key: SecretKey,
+    refuse: bool,
+    requests: Mutex<Vec<SigningRequest>>,
+}
+
+impl MockSigner {
+    /// Create a `MockSigner` that signs with the `SecretKey`
+    #[must_use]
+    pub fn new(secret_key: SecretKey) -> MockSigner {
+        MockSigner {
+            secret_key,
+            refuse: false,
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Create a `MockSigner` that records requests but refuses to sign them
+    #[must_use]
+    pub fn new_refusing(secret_key: SecretKey) -> MockSigner {
+        MockSigner {
+            refuse: true,
+            ..MockSigner::new(secret_key)
+        }
+    }
+
+    /// The `PublicKey` of the signer
+    #[must_use]
+    pub fn public(&self) -> PublicKey {
+        self.secret_key.public()
+    }
+
+    /// The requests made so far, oldest first
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn requests(&self) -> Vec<SigningRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    fn request(&self, request: SigningRequest) -> Result<(), Error> {
+        self.requests.lock().unwrap().push(request);
+        if self.refuse {
+            Err(InnerError::General("MockSigner refused to sign".to_owned()).into())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl SignerIdentity for MockSigner {
+    fn signature_scheme(&self) -> SignatureScheme {
+        SignatureScheme::Ed25519
+    }
+
+    fn signing_key_bytes(&self) -> [u8; 32] {
+        self.secret_key.public().to_bytes()
+    }
+}
+
+impl Signer for MockSigner {
+    fn sign_record_digest(&self, digest: &[u8; 64]) -> Result<RecordSignature, Error> {
+        self.request(SigningRequest::RecordDigest(*digest))?;
+        self.secret_key.sign_record_digest(digest)
+    }
+
+    fn sign_dht_item(&self, signable: &[u8]) -> Result<DalekSignature, Error> {
+        self.request(SigningRequest::DhtItem(signable.to_owned()))?;
+        self.secret_key.sign_dht_item(signable)
+    }
+}
+
+// Assemble a signed Mainline DHT mutable item. The signature is checked, so a
+// misbehaving signer is caught here rather than by DHT nodes.
+pub(crate) async fn sign_mutable_item<S: AsyncSigner + ?Sized>(
+    signer: &S,
+    value: &[u8],
+    seq: i64,
+    salt: Option<&[u8]>,
+) -> Result<MutableItem, Error> {
+    let key = signer.signing_key_bytes();
+    let signable = dht_signable(value, seq, salt);
+    let signature = signer.sign_dht_item_async(&signable).await?;
+    PublicKey::from_bytes(&key)?
+        .to_verifying_key()
+        .verify_strict(&signable, &signature)?;
+    Ok(MutableItem::new_signed_unchecked(
+        key,
+        signature.to_bytes(),
+        value,
+        seq,
+        salt,
+    ))
+}
+
+// The bencoded data signed for a mutable item, as per BEP 44
+fn dht_signable(value: &[u8], seq: i64, salt: Option<&[u8]>) -> Vec<u8> {
+    let mut signable = Vec::new();
+    if let Some(salt) = salt {
+        signable.extend(format!("4:salt{}:", salt.len()).into_bytes());
+        signable.extend(salt);
+    }
+    signable.extend(format!("3:seqi{seq}e1:v{}:", value.len()).into_bytes());
+    signable.extend(value);
+    signable
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn test_signer_record() {
+        let secret_key = SecretKey::generate();
+        let timestamp = Timestamp::from_unixtime(1_700_000_000, 0).unwrap();
+        let signer = Arc::new(MockSigner::new(secret_key.clone()));
+
+        let record = RecordBuilder::new_with_signer(Kind::EXAMPLE, signer.public(), signer.clone())
+            .payload(b"Hello World!")
+            .unwrap()
+            .timestamp(timestamp)
+            .deterministic_address(b"hello")
+            .sign()
+            .unwrap();
+        record.verify().unwrap();
+        assert_eq!(signer.requests().len(), 1);
+
+        // Ed25519 signing is deterministic, so this is the same record as if
+        // the key were used directly
+        let direct = RecordBuilder::new(Kind::EXAMPLE, secret_key.clone())
+            .payload(b"Hello World!")
+            .unwrap()
+            .timestamp(timestamp)
+            .deterministic_address(b"hello")
+            .sign()
+            .unwrap();
+        assert_eq!(record, direct);
+
+        // Refusals are reported
+        let signer = Arc::new(MockSigner::new_refusing(secret_key));
+        assert!(
+            RecordBuilder::new_with_signer(Kind::EXAMPLE, signer.public(), signer.clone())
+                .timestamp(timestamp)
+                .sign()
+                .is_err()
+        );
+        assert_eq!(signer.requests().len(), 1);
+    }
+
+    #[test]
+    fn test_signer_record_async() {
+        let schnorr_key = SchnorrSigningKey::random(&mut scrypt::password_hash::rand_core::OsRng);
+        let author = SecretKey::generate().public();
+        let builder = RecordBuilder::with_signing_data(
+            Kind::EXAMPLE,
+            author,
+            RecordSigningData::Secp256k1PublicKey(*schnorr_key.verifying_key()),
+        )
+        .timestamp(Timestamp::from_unixtime(1_700_000_000, 0).unwrap());
+
+        let record = futures::executor::block_on(builder.clone().sign_async(&schnorr_key)).unwrap();
+        record.verify().unwrap();
+        assert_eq!(record.author_public_key(), author);
+        assert_eq!(
+            record.flags().get_signature_scheme(),
+            SignatureScheme::Secp256k1
+        );
+
+        // The signer must hold the signing key
+        let other = SchnorrSigningKey::random(&mut scrypt::password_hash::rand_core::OsRng);
+        assert!(futures::executor::block_on(builder.clone().sign_async(&other)).is_err());
+        let e = futures::executor::block_on(builder.clone().sign_async(&SecretKey::generate()))
+            .unwrap_err();
+        assert!(matches!(e.inner, InnerError::SignerSchemeMismatch));
+
+        // A key alone can't sign in-process
+        let e = builder.sign().unwrap_err();
+        assert!(matches!(e.inner, InnerError::MissingSignature));
+    }
+
+    #[test]
+    fn test_signer_dht_item() {
+        let secret_key = SecretKey::generate();
+        let signer = MockSigner::new(secret_key.clone());
+
+        let item =
+            futures::executor::block_on(sign_mutable_item(&signer, b"value", 3, Some(b"salt")))
+                .unwrap();
+
+        // The same item as mainline assembles from the key itself
+        let expected = MutableItem::new(secret_key.to_signing_key(), b"value", 3, Some(b"salt"));
+        assert_eq!(item, expected);
+        assert_eq!(
+            signer.requests(),
+            vec![SigningRequest::DhtItem(
+                b"4:salt4:salt3:seqi3e1:v5:value".to_vec()
+            )]
+        );
+
+        // secp256k1 keys can't sign DHT items
+        let schnorr_key = SchnorrSigningKey::random(&mut scrypt::password_hash::rand_core::OsRng);
+        assert!(
+            futures::executor::block_on(sign_mutable_item(&schnorr_key, b"value", 1, None))
+                .is_err()
+        );
+    }
+}
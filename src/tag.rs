@@ -1,10 +1,12 @@
-use crate::{Error, InnerError, Kind, PublicKey, Reference};
+use crate::{Error, InnerError, Kind, PublicKey, Reference, Timestamp};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use std::ops::{Deref, DerefMut};
 
 /// A type of tag
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+///
+/// Applications may define their own, see `TagType::register()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct TagType(pub u16);
 
@@ -41,11 +43,49 @@ impl TagType {
 
     /// [Content Segment: Video](https://stevefarroll.github.io/mosaic-spec/core_tags/#content-segment-video)
     pub const CONTENT_SEGMENT_VIDEO: TagType = TagType(0x26);
+
+    /// Hashtag: a topic, stored without the `#` and with ASCII in lowercase
+    pub const HASHTAG: TagType = TagType(0x30);
+
+    /// Subject: a title or subject line
+    pub const SUBJECT: TagType = TagType(0x31);
+
+    /// Expiration: a `Timestamp` after which the record may be deleted
+    pub const EXPIRATION: TagType = TagType(0x32);
+
+    /// Content Warning: the content should be hidden until the reader
+    /// chooses to see it, with an optional reason
+    pub const CONTENT_WARNING: TagType = TagType(0x33);
+
+    /// Language: a BCP 47 language code (e.g. `en` or `pt-BR`) of the content
+    pub const LANGUAGE: TagType = TagType(0x34);
+
+    /// Geohash: a location, as a geohash of 1 to 12 characters
+    pub const GEOHASH: TagType = TagType(0x35);
+
+    /// Summary: a short summary of the content, for listings
+    pub const SUMMARY: TagType = TagType(0x36);
+
+    /// Slug: the short name that the record's deterministic address was made
+    /// from (e.g. for URLs)
+    pub const SLUG: TagType = TagType(0x37);
+
+    /// Cover Image Url: an image to show with the content, on the web
+    pub const COVER_IMAGE_URL: TagType = TagType(0x38);
+
+    /// Cover Image Blob: an image to show with the content, by its blob hash
+    pub const COVER_IMAGE_BLOB: TagType = TagType(0x39);
+
+    /// Published At: a `Timestamp` when the content was first published
+    pub const PUBLISHED_AT: TagType = TagType(0x3a);
 }
 
 impl std::fmt::Display for TagType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{self:?}")
+        match self.name() {
+            Some(name) => write!(f, "{name}"),
+            None => write!(f, "{self:?}"),
+        }
     }
 }
 
@@ -53,6 +93,62 @@ impl TagType {
     fn into_u16(self) -> u16 {
         self.0
     }
+
+    /// The name of the tag type, if it is defined by this crate or registered
+    #[must_use]
+    pub fn name(&self) -> Option<&'static str> {
+        self.built_in_name()
+            .or_else(|| self.registration().map(|r| r.name))
+    }
+
+    /// Is this a tag type defined by this crate?
+    #[must_use]
+    pub fn is_built_in(&self) -> bool {
+        self.built_in_name().is_some()
+    }
+
+    fn built_in_name(self) -> Option<&'static str> {
+        Some(match self {
+            TagType::NOTIFY_PUBLIC_KEY => "Notify Public Key",
+            TagType::REPLY => "Reply",
+            TagType::ROOT => "Root",
+            TagType::NOSTR_SISTER => "Nostr Sister",
+            TagType::SUBKEY => "Subkey",
+            TagType::CONTENT_SEGMENT_USER_MENTION => "User Mention",
+            TagType::CONTENT_SEGMENT_SERVER_MENTION => "Server Mention",
+            TagType::CONTENT_SEGMENT_QUOTE => "Quote",
+            TagType::CONTENT_SEGMENT_URL => "Url",
+            TagType::CONTENT_SEGMENT_IMAGE => "Image",
+            TagType::CONTENT_SEGMENT_VIDEO => "Video",
+            TagType::HASHTAG => "Hashtag",
+            TagType::SUBJECT => "Subject",
+            TagType::EXPIRATION => "Expiration",
+            TagType::CONTENT_WARNING => "Content Warning",
+            TagType::LANGUAGE => "Language",
+            TagType::GEOHASH => "Geohash",
+            TagType::SUMMARY => "Summary",
+            TagType::SLUG => "Slug",
+            TagType::COVER_IMAGE_URL => "Cover Image Url",
+            TagType::COVER_IMAGE_BLOB => "Cover Image Blob",
+            TagType::PUBLISHED_AT => "Published At",
+            _ => return None,
+        })
+    }
+
+    /// Is this one of the `CONTENT_SEGMENT_*` types, which mark a segment at
+    /// an offset into the payload?
+    #[must_use]
+    pub fn is_content_segment(&self) -> bool {
+        matches!(
+            *self,
+            TagType::CONTENT_SEGMENT_USER_MENTION
+                | TagType::CONTENT_SEGMENT_SERVER_MENTION
+                | TagType::CONTENT_SEGMENT_QUOTE
+                | TagType::CONTENT_SEGMENT_URL
+                | TagType::CONTENT_SEGMENT_IMAGE
+                | TagType::CONTENT_SEGMENT_VIDEO
+        )
+    }
 }
 
 /// A single `Tag`, unsized (borrowed)
@@ -83,7 +179,8 @@ impl Tag {
     ///
     /// # Errors
     ///
-    /// Errors if the input isn't long enough or the region is padding.
+    /// Errors if the input isn't long enough, the region is padding, or the
+    /// length is too short to hold the length and type.
     #[allow(clippy::missing_panics_doc)]
     pub fn from_bytes(input: &[u8]) -> Result<&Tag, Error> {
         if input.len() < 4 {
@@ -93,6 +190,9 @@ impl Tag {
             return Err(InnerError::Padding.into());
         }
         let datalen = u16::from_le_bytes(input[0..2].try_into().unwrap()) as usize;
+        if datalen < 4 {
+            return Err(InnerError::InvalidTag.into());
+        }
         if input.len() < datalen {
             return Err(InnerError::EndOfInput.into());
         }
@@ -210,6 +310,286 @@ impl Tag {
         }
     }
 
+    /// Get the hashtag (for `HASHTAG` tags only)
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the tag value is not UTF-8
+    pub fn get_hashtag(&self) -> Result<Option<&str>, Error> {
+        self.get_text(TagType::HASHTAG)
+    }
+
+    /// Get the subject (for `SUBJECT` tags only)
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the tag value is not UTF-8
+    pub fn get_subject(&self) -> Result<Option<&str>, Error> {
+        self.get_text(TagType::SUBJECT)
+    }
+
+    /// Get the expiration (for `EXPIRATION` tags only)
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the tag value is not a valid `Timestamp`
+    pub fn get_expiration(&self) -> Result<Option<Timestamp>, Error> {
+        self.get_timestamp(TagType::EXPIRATION)
+    }
+
+    /// Get the content warning reason, which may be empty (for
+    /// `CONTENT_WARNING` tags only)
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the tag value is not UTF-8
+    pub fn get_content_warning(&self) -> Result<Option<&str>, Error> {
+        self.get_text(TagType::CONTENT_WARNING)
+    }
+
+    /// Get the language code (for `LANGUAGE` tags only)
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the tag value is not UTF-8
+    pub fn get_language(&self) -> Result<Option<&str>, Error> {
+        self.get_text(TagType::LANGUAGE)
+    }
+
+    /// Get the geohash (for `GEOHASH` tags only)
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the tag value is not UTF-8
+    pub fn get_geohash(&self) -> Result<Option<&str>, Error> {
+        self.get_text(TagType::GEOHASH)
+    }
+
+    /// Get the summary (for `SUMMARY` tags only)
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the tag value is not UTF-8
+    pub fn get_summary(&self) -> Result<Option<&str>, Error> {
+        self.get_text(TagType::SUMMARY)
+    }
+
+    /// Get the slug (for `SLUG` tags only)
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the tag value is not UTF-8
+    pub fn get_slug(&self) -> Result<Option<&str>, Error> {
+        self.get_text(TagType::SLUG)
+    }
+
+    /// Get the cover image URL (for `COVER_IMAGE_URL` tags only)
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the tag value is not UTF-8
+    pub fn get_cover_image_url(&self) -> Result<Option<&str>, Error> {
+        self.get_text(TagType::COVER_IMAGE_URL)
+    }
+
+    /// Get the cover image blob hash (for `COVER_IMAGE_BLOB` tags only)
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the tag is not 40 bytes long
+    pub fn get_cover_image_blob(&self) -> Result<Option<[u8; 32]>, Error> {
+        if self.get_type() != TagType::COVER_IMAGE_BLOB {
+            return Ok(None);
+        }
+        match self.0.get(8..) {
+            Some(bytes) if bytes.len() == 32 => Ok(Some(bytes.try_into()?)),
+            _ => Err(InnerError::InvalidTag.into()),
+        }
+    }
+
+    /// Get the publication time (for `PUBLISHED_AT` tags only)
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the tag value is not a valid `Timestamp`
+    pub fn get_published_at(&self) -> Result<Option<Timestamp>, Error> {
+        self.get_timestamp(TagType::PUBLISHED_AT)
+    }
+
+    // The timestamp after the 4 reserved bytes, if the tag is of the type
+    fn get_timestamp(&self, ty: TagType) -> Result<Option<Timestamp>, Error> {
+        if self.get_type() != ty {
+            return Ok(None);
+        }
+        match self.0.get(8..16) {
+            Some(bytes) if self.0.len() == 16 => {
+                Ok(Some(Timestamp::from_bytes(bytes.try_into()?)?))
+            }
+            _ => Err(InnerError::InvalidTag.into()),
+        }
+    }
+
+    // The text after the 4 reserved bytes, if the tag is of the type
+    fn get_text(&self, ty: TagType) -> Result<Option<&str>, Error> {
+        if self.get_type() != ty {
+            return Ok(None);
+        }
+        match self.0.get(8..) {
+            Some(bytes) => Ok(Some(std::str::from_utf8(bytes)?)),
+            None => Err(InnerError::InvalidTag.into()),
+        }
+    }
+
+    /// Check that the tag is valid for its type: that it parses (see
+    /// `parse()`), or that its registered validator accepts it (see
+    /// `TagType::register()`). Tags of other types are not checked.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the tag is not valid
+    pub fn validate(&self) -> Result<(), Error> {
+        match self.get_type().registration() {
+            Some(registration) => (registration.validate)(self.data_bytes()),
+            None => self.parse().map(|_| ()),
+        }
+    }
+
+    /// Parse into a `ParsedTag` according to the type
+    ///
+    /// Tags of types this crate does not know are `ParsedTag::Unknown`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the tag is the wrong length for its type, if its
+    /// reserved bytes are not zero, or if its public key, reference or URL is
+    /// invalid.
+    #[allow(clippy::missing_panics_doc)]
+    #[allow(clippy::too_many_lines)]
+    pub fn parse(&self) -> Result<ParsedTag<'_>, Error> {
+        let ty = self.get_type();
+        let len = self.0.len();
+        let len_ok = match ty {
+            TagType::NOTIFY_PUBLIC_KEY
+            | TagType::NOSTR_SISTER
+            | TagType::SUBKEY
+            | TagType::CONTENT_SEGMENT_USER_MENTION
+            | TagType::CONTENT_SEGMENT_SERVER_MENTION
+            | TagType::COVER_IMAGE_BLOB => len == 40,
+            TagType::REPLY | TagType::ROOT | TagType::CONTENT_SEGMENT_QUOTE => len == 64,
+            TagType::EXPIRATION | TagType::PUBLISHED_AT => len == 16,
+            TagType::CONTENT_SEGMENT_URL
+            | TagType::CONTENT_SEGMENT_IMAGE
+            | TagType::CONTENT_SEGMENT_VIDEO
+            | TagType::HASHTAG
+            | TagType::SUBJECT
+            | TagType::CONTENT_WARNING
+            | TagType::LANGUAGE
+            | TagType::GEOHASH
+            | TagType::SUMMARY
+            | TagType::SLUG
+            | TagType::COVER_IMAGE_URL => len >= 8,
+            _ => true,
+        };
+        if !len_ok {
+            return Err(InnerError::InvalidTag.into());
+        }
+
+        // Types without an offset reserve bytes 4..8
+        let has_reserved = matches!(
+            ty,
+            TagType::NOTIFY_PUBLIC_KEY
+                | TagType::REPLY
+                | TagType::ROOT
+                | TagType::NOSTR_SISTER
+                | TagType::SUBKEY
+                | TagType::HASHTAG
+                | TagType::SUBJECT
+                | TagType::EXPIRATION
+                | TagType::CONTENT_WARNING
+                | TagType::LANGUAGE
+                | TagType::GEOHASH
+                | TagType::SUMMARY
+                | TagType::SLUG
+                | TagType::COVER_IMAGE_URL
+                | TagType::COVER_IMAGE_BLOB
+                | TagType::PUBLISHED_AT
+        );
+        if has_reserved && self.0[4..8] != [0; 4] {
+            return Err(InnerError::ReservedSpaceUsed.into());
+        }
+
+        let offset = || u32::from_le_bytes(self.0[4..8].try_into().unwrap());
+        let public_key = || PublicKey::from_bytes(self.0[8..40].try_into().unwrap());
+        let reference = || Reference::from_bytes(self.0[16..64].try_into().unwrap());
+        let kind = || Kind::from_bytes(self.0[8..16].try_into().unwrap());
+        let text = || std::str::from_utf8(&self.0[8..]);
+
+        Ok(match ty {
+            TagType::NOTIFY_PUBLIC_KEY => ParsedTag::NotifyPublicKey(public_key()?),
+            TagType::REPLY => ParsedTag::Reply {
+                reference: reference()?,
+                kind: kind(),
+            },
+            TagType::ROOT => ParsedTag::Root {
+                reference: reference()?,
+                kind: kind(),
+            },
+            TagType::NOSTR_SISTER => ParsedTag::NostrSister(self.0[8..40].try_into().unwrap()),
+            TagType::SUBKEY => ParsedTag::Subkey(public_key()?),
+            TagType::CONTENT_SEGMENT_USER_MENTION => ParsedTag::UserMention {
+                public_key: public_key()?,
+                offset: offset(),
+            },
+            TagType::CONTENT_SEGMENT_SERVER_MENTION => ParsedTag::ServerMention {
+                public_key: public_key()?,
+                offset: offset(),
+            },
+            TagType::CONTENT_SEGMENT_QUOTE => ParsedTag::Quote {
+                reference: reference()?,
+                kind: kind(),
+                offset: offset(),
+            },
+            TagType::CONTENT_SEGMENT_URL => ParsedTag::Url {
+                url: text()?,
+                offset: offset(),
+            },
+            TagType::CONTENT_SEGMENT_IMAGE => ParsedTag::Image {
+                url: text()?,
+                offset: offset(),
+            },
+            TagType::CONTENT_SEGMENT_VIDEO => ParsedTag::Video {
+                url: text()?,
+                offset: offset(),
+            },
+            TagType::HASHTAG => {
+                let hashtag = text()?;
+                check_hashtag(hashtag)?;
+                ParsedTag::Hashtag(hashtag)
+            }
+            TagType::SUBJECT => ParsedTag::Subject(text()?),
+            TagType::EXPIRATION => ParsedTag::Expiration(self.get_expiration()?.unwrap()),
+            TagType::CONTENT_WARNING => ParsedTag::ContentWarning(text()?),
+            TagType::LANGUAGE => {
+                let language = text()?;
+                check_language(language)?;
+                ParsedTag::Language(language)
+            }
+            TagType::GEOHASH => {
+                let geohash = text()?;
+                check_geohash(geohash)?;
+                ParsedTag::Geohash(geohash)
+            }
+            TagType::SUMMARY => ParsedTag::Summary(text()?),
+            TagType::SLUG => ParsedTag::Slug(text()?),
+            TagType::COVER_IMAGE_URL => ParsedTag::CoverImageUrl(text()?),
+            TagType::COVER_IMAGE_BLOB => {
+                ParsedTag::CoverImageBlob(self.0[8..40].try_into().unwrap())
+            }
+            TagType::PUBLISHED_AT => ParsedTag::PublishedAt(self.get_published_at()?.unwrap()),
+            _ => ParsedTag::Unknown(ty, self.data_bytes()),
+        })
+    }
+
     /// Write a new `NOTIFY_PUBLIC_KEY` tag to the buffer
     ///
     /// # Errors
@@ -456,6 +836,179 @@ impl Tag {
         buffer[8..len].copy_from_slice(url.as_bytes());
         Ok(Tag::from_inner(&buffer[..len]))
     }
+
+    /// Create a new `HASHTAG` tag
+    ///
+    /// A leading `#` is dropped and ASCII letters are lowercased, so that the
+    /// same hashtag always makes the same tag (and so matches in filters).
+    ///
+    /// # Errors
+    ///
+    /// Errors if the buffer isn't long enough, or if the hashtag is empty or
+    /// too long.
+    #[allow(clippy::missing_panics_doc)]
+    pub fn write_hashtag<'a>(buffer: &'a mut [u8], hashtag: &str) -> Result<&'a Tag, Error> {
+        let hashtag = hashtag.strip_prefix('#').unwrap_or(hashtag);
+        let len = Tag::write_value(buffer, TagType::HASHTAG, hashtag.as_bytes())?;
+        buffer[8..len].make_ascii_lowercase();
+        let tag = Tag::from_inner(&buffer[..len]);
+        check_hashtag(tag.get_hashtag()?.unwrap_or_default())?;
+        Ok(tag)
+    }
+
+    /// Create a new `SUBJECT` tag
+    ///
+    /// # Errors
+    ///
+    /// Errors if the buffer isn't long enough, or if the subject is too long.
+    pub fn write_subject<'a>(buffer: &'a mut [u8], subject: &str) -> Result<&'a Tag, Error> {
+        let len = Tag::write_value(buffer, TagType::SUBJECT, subject.as_bytes())?;
+        Ok(Tag::from_inner(&buffer[..len]))
+    }
+
+    /// Create a new `EXPIRATION` tag
+    ///
+    /// # Errors
+    ///
+    /// Errors if the buffer isn't long enough.
+    pub fn write_expiration(buffer: &mut [u8], expiration: Timestamp) -> Result<&Tag, Error> {
+        let len = Tag::write_value(buffer, TagType::EXPIRATION, &expiration.to_bytes())?;
+        Ok(Tag::from_inner(&buffer[..len]))
+    }
+
+    /// Create a new `CONTENT_WARNING` tag. The reason may be empty.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the buffer isn't long enough, or if the reason is too long.
+    pub fn write_content_warning<'a>(buffer: &'a mut [u8], reason: &str) -> Result<&'a Tag, Error> {
+        let len = Tag::write_value(buffer, TagType::CONTENT_WARNING, reason.as_bytes())?;
+        Ok(Tag::from_inner(&buffer[..len]))
+    }
+
+    /// Create a new `LANGUAGE` tag
+    ///
+    /// # Errors
+    ///
+    /// Errors if the buffer isn't long enough, or if the language code is not
+    /// of the form of a BCP 47 code (ASCII letters, digits and hyphens).
+    pub fn write_language<'a>(buffer: &'a mut [u8], language: &str) -> Result<&'a Tag, Error> {
+        check_language(language)?;
+        let len = Tag::write_value(buffer, TagType::LANGUAGE, language.as_bytes())?;
+        Ok(Tag::from_inner(&buffer[..len]))
+    }
+
+    /// Create a new `GEOHASH` tag
+    ///
+    /// # Errors
+    ///
+    /// Errors if the buffer isn't long enough, or if the geohash is not 1 to
+    /// 12 characters of the geohash alphabet.
+    pub fn write_geohash<'a>(buffer: &'a mut [u8], geohash: &str) -> Result<&'a Tag, Error> {
+        check_geohash(geohash)?;
+        let len = Tag::write_value(buffer, TagType::GEOHASH, geohash.as_bytes())?;
+        Ok(Tag::from_inner(&buffer[..len]))
+    }
+
+    /// Create a new `SUMMARY` tag
+    ///
+    /// # Errors
+    ///
+    /// Errors if the buffer isn't long enough, or if the summary is too long.
+    pub fn write_summary<'a>(buffer: &'a mut [u8], summary: &str) -> Result<&'a Tag, Error> {
+        let len = Tag::write_value(buffer, TagType::SUMMARY, summary.as_bytes())?;
+        Ok(Tag::from_inner(&buffer[..len]))
+    }
+
+    /// Create a new `SLUG` tag
+    ///
+    /// # Errors
+    ///
+    /// Errors if the buffer isn't long enough, or if the slug is too long.
+    pub fn write_slug<'a>(buffer: &'a mut [u8], slug: &str) -> Result<&'a Tag, Error> {
+        let len = Tag::write_value(buffer, TagType::SLUG, slug.as_bytes())?;
+        Ok(Tag::from_inner(&buffer[..len]))
+    }
+
+    /// Create a new `COVER_IMAGE_URL` tag
+    ///
+    /// # Errors
+    ///
+    /// Errors if the buffer isn't long enough, or if the URL is too long.
+    pub fn write_cover_image_url<'a>(buffer: &'a mut [u8], url: &str) -> Result<&'a Tag, Error> {
+        let len = Tag::write_value(buffer, TagType::COVER_IMAGE_URL, url.as_bytes())?;
+        Ok(Tag::from_inner(&buffer[..len]))
+    }
+
+    /// Create a new `COVER_IMAGE_BLOB` tag
+    ///
+    /// # Errors
+    ///
+    /// Errors if the buffer isn't long enough.
+    pub fn write_cover_image_blob<'a>(
+        buffer: &'a mut [u8],
+        hash: &[u8; 32],
+    ) -> Result<&'a Tag, Error> {
+        let len = Tag::write_value(buffer, TagType::COVER_IMAGE_BLOB, hash)?;
+        Ok(Tag::from_inner(&buffer[..len]))
+    }
+
+    /// Create a new `PUBLISHED_AT` tag
+    ///
+    /// # Errors
+    ///
+    /// Errors if the buffer isn't long enough.
+    pub fn write_published_at(buffer: &mut [u8], published_at: Timestamp) -> Result<&Tag, Error> {
+        let len = Tag::write_value(buffer, TagType::PUBLISHED_AT, &published_at.to_bytes())?;
+        Ok(Tag::from_inner(&buffer[..len]))
+    }
+
+    // Write a tag whose value follows 4 zeroed bytes, returning its length
+    fn write_value(buffer: &mut [u8], ty: TagType, value: &[u8]) -> Result<usize, Error> {
+        let len: usize = 8 + value.len();
+        if len > 65535 {
+            return Err(InnerError::TagTooLong.into());
+        }
+        if buffer.len() < len {
+            return Err(InnerError::EndOfOutput.into());
+        }
+        buffer[0..2].copy_from_slice(u16::try_from(len)?.to_le_bytes().as_slice());
+        buffer[2..4].copy_from_slice(ty.0.to_le_bytes().as_slice());
+        buffer[4..8].fill(0);
+        buffer[8..len].copy_from_slice(value);
+        Ok(len)
+    }
+}
+
+// Hashtags are stored in the form `write_hashtag()` puts them in
+fn check_hashtag(hashtag: &str) -> Result<(), Error> {
+    if hashtag.is_empty()
+        || hashtag.starts_with('#')
+        || hashtag.bytes().any(|b| b.is_ascii_uppercase())
+    {
+        return Err(InnerError::InvalidTag.into());
+    }
+    Ok(())
+}
+
+// BCP 47 codes are hyphen-separated subtags of ASCII letters and digits
+fn check_language(language: &str) -> Result<(), Error> {
+    if language.len() > 35
+        || language
+            .split('-')
+            .any(|subtag| subtag.is_empty() || !subtag.bytes().all(|b| b.is_ascii_alphanumeric()))
+    {
+        return Err(InnerError::InvalidTag.into());
+    }
+    Ok(())
+}
+
+fn check_geohash(geohash: &str) -> Result<(), Error> {
+    const ALPHABET: &[u8] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+    if geohash.is_empty() || geohash.len() > 12 || !geohash.bytes().all(|b| ALPHABET.contains(&b)) {
+        return Err(InnerError::InvalidTag.into());
+    }
+    Ok(())
 }
 
 /// A single `OwnedTag`
@@ -614,6 +1167,148 @@ impl OwnedTag {
         let _ = Tag::write_content_segment_video(&mut bytes, url, offset).unwrap();
         OwnedTag(bytes)
     }
+
+    /// Create a new `HASHTAG` tag
+    ///
+    /// To avoid copies, consider `Tag::write_hashtag()`
+    ///
+    /// # Errors
+    ///
+    /// Errors if the hashtag is empty or too long.
+    pub fn new_hashtag(hashtag: &str) -> Result<OwnedTag, Error> {
+        let mut bytes: Vec<u8> = vec![0; 8 + hashtag.len()];
+        let len = Tag::write_hashtag(&mut bytes, hashtag)?.as_bytes().len();
+        bytes.truncate(len);
+        Ok(OwnedTag(bytes))
+    }
+
+    /// Create a new `SUBJECT` tag
+    ///
+    /// To avoid copies, consider `Tag::write_subject()`
+    ///
+    /// # Errors
+    ///
+    /// Errors if the subject is too long.
+    pub fn new_subject(subject: &str) -> Result<OwnedTag, Error> {
+        let mut bytes: Vec<u8> = vec![0; 8 + subject.len()];
+        let _ = Tag::write_subject(&mut bytes, subject)?;
+        Ok(OwnedTag(bytes))
+    }
+
+    /// Create a new `EXPIRATION` tag
+    ///
+    /// To avoid copies, consider `Tag::write_expiration()`
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn new_expiration(expiration: Timestamp) -> OwnedTag {
+        const LEN: usize = 16;
+        let mut bytes: Vec<u8> = vec![0; LEN];
+        let _ = Tag::write_expiration(&mut bytes, expiration).unwrap();
+        OwnedTag(bytes)
+    }
+
+    /// Create a new `CONTENT_WARNING` tag. The reason may be empty.
+    ///
+    /// To avoid copies, consider `Tag::write_content_warning()`
+    ///
+    /// # Errors
+    ///
+    /// Errors if the reason is too long.
+    pub fn new_content_warning(reason: &str) -> Result<OwnedTag, Error> {
+        let mut bytes: Vec<u8> = vec![0; 8 + reason.len()];
+        let _ = Tag::write_content_warning(&mut bytes, reason)?;
+        Ok(OwnedTag(bytes))
+    }
+
+    /// Create a new `LANGUAGE` tag
+    ///
+    /// To avoid copies, consider `Tag::write_language()`
+    ///
+    /// # Errors
+    ///
+    /// Errors if the language code is not of the form of a BCP 47 code.
+    pub fn new_language(language: &str) -> Result<OwnedTag, Error> {
+        let mut bytes: Vec<u8> = vec![0; 8 + language.len()];
+        let _ = Tag::write_language(&mut bytes, language)?;
+        Ok(OwnedTag(bytes))
+    }
+
+    /// Create a new `GEOHASH` tag
+    ///
+    /// To avoid copies, consider `Tag::write_geohash()`
+    ///
+    /// # Errors
+    ///
+    /// Errors if the geohash is not 1 to 12 characters of the geohash
+    /// alphabet.
+    pub fn new_geohash(geohash: &str) -> Result<OwnedTag, Error> {
+        let mut bytes: Vec<u8> = vec![0; 8 + geohash.len()];
+        let _ = Tag::write_geohash(&mut bytes, geohash)?;
+        Ok(OwnedTag(bytes))
+    }
+
+    /// Create a new `SUMMARY` tag
+    ///
+    /// To avoid copies, consider `Tag::write_summary()`
+    ///
+    /// # Errors
+    ///
+    /// Errors if the summary is too long.
+    pub fn new_summary(summary: &str) -> Result<OwnedTag, Error> {
+        let mut bytes: Vec<u8> = vec![0; 8 + summary.len()];
+        let _ = Tag::write_summary(&mut bytes, summary)?;
+        Ok(OwnedTag(bytes))
+    }
+
+    /// Create a new `SLUG` tag
+    ///
+    /// To avoid copies, consider `Tag::write_slug()`
+    ///
+    /// # Errors
+    ///
+    /// Errors if the slug is too long.
+    pub fn new_slug(slug: &str) -> Result<OwnedTag, Error> {
+        let mut bytes: Vec<u8> = vec![0; 8 + slug.len()];
+        let _ = Tag::write_slug(&mut bytes, slug)?;
+        Ok(OwnedTag(bytes))
+    }
+
+    /// Create a new `COVER_IMAGE_URL` tag
+    ///
+    /// To avoid copies, consider `Tag::write_cover_image_url()`
+    ///
+    /// # Errors
+    ///
+    /// Errors if the URL is too long.
+    pub fn new_cover_image_url(url: &str) -> Result<OwnedTag, Error> {
+        let mut bytes: Vec<u8> = vec![0; 8 + url.len()];
+        let _ = Tag::write_cover_image_url(&mut bytes, url)?;
+        Ok(OwnedTag(bytes))
+    }
+
+    /// Create a new `COVER_IMAGE_BLOB` tag
+    ///
+    /// To avoid copies, consider `Tag::write_cover_image_blob()`
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn new_cover_image_blob(hash: &[u8; 32]) -> OwnedTag {
+        const LEN: usize = 40;
+        let mut bytes: Vec<u8> = vec![0; LEN];
+        let _ = Tag::write_cover_image_blob(&mut bytes, hash).unwrap();
+        OwnedTag(bytes)
+    }
+
+    /// Create a new `PUBLISHED_AT` tag
+    ///
+    /// To avoid copies, consider `Tag::write_published_at()`
+    #[allow(clippy::missing_panics_doc)]
+    #[must_use]
+    pub fn new_published_at(published_at: Timestamp) -> OwnedTag {
+        const LEN: usize = 16;
+        let mut bytes: Vec<u8> = vec![0; LEN];
+        let _ = Tag::write_published_at(&mut bytes, published_at).unwrap();
+        OwnedTag(bytes)
+    }
 }
 
 impl Deref for OwnedTag {
@@ -642,6 +1337,284 @@ impl AsMut<Tag> for OwnedTag {
     }
 }
 
+impl std::fmt::Display for Tag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ty = self.get_type();
+        if let Some(registration) = ty.registration() {
+            write!(f, "{}: ", registration.name)?;
+            return match (registration.validate)(self.data_bytes()) {
+                Ok(()) => (registration.format)(self.data_bytes(), f),
+                Err(_) => write!(f, "(invalid) {}", z32::encode(self.data_bytes())),
+            };
+        }
+        match self.parse() {
+            Ok(parsed) => write!(f, "{parsed}"),
+            Err(_) => write!(f, "{ty}: (invalid) {}", z32::encode(self.data_bytes())),
+        }
+    }
+}
+
+/// A `Tag` parsed according to its `TagType`
+///
+/// See `Tag::parse()`. Convert back with `to_owned_tag()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParsedTag<'a> {
+    /// A `NOTIFY_PUBLIC_KEY` tag
+    NotifyPublicKey(PublicKey),
+
+    /// A `REPLY` tag
+    Reply {
+        /// The record replied to
+        reference: Reference,
+
+        /// The kind of the record replied to
+        kind: Kind,
+    },
+
+    /// A `ROOT` tag
+    Root {
+        /// The root record of the thread
+        reference: Reference,
+
+        /// The kind of the root record
+        kind: Kind,
+    },
+
+    /// A `NOSTR_SISTER` tag, with the nostr event id
+    NostrSister([u8; 32]),
+
+    /// A `SUBKEY` tag
+    Subkey(PublicKey),
+
+    /// A `CONTENT_SEGMENT_USER_MENTION` tag
+    UserMention {
+        /// The user mentioned
+        public_key: PublicKey,
+
+        /// The offset into the payload
+        offset: u32,
+    },
+
+    /// A `CONTENT_SEGMENT_SERVER_MENTION` tag
+    ServerMention {
+        /// The server mentioned
+        public_key: PublicKey,
+
+        /// The offset into the payload
+        offset: u32,
+    },
+
+    /// A `CONTENT_SEGMENT_QUOTE` tag
+    Quote {
+        /// The record quoted
+        reference: Reference,
+
+        /// The kind of the record quoted
+        kind: Kind,
+
+        /// The offset into the payload
+        offset: u32,
+    },
+
+    /// A `CONTENT_SEGMENT_URL` tag
+    Url {
+        /// The URL
+        url: &'a str,
+
+        /// The offset into the payload
+        offset: u32,
+    },
+
+    /// A `CONTENT_SEGMENT_IMAGE` tag
+    Image {
+        /// The URL of the image
+        url: &'a str,
+
+        /// The offset into the payload
+        offset: u32,
+    },
+
+    /// A `CONTENT_SEGMENT_VIDEO` tag
+    Video {
+        /// The URL of the video
+        url: &'a str,
+
+        /// The offset into the payload
+        offset: u32,
+    },
+
+    /// A `HASHTAG` tag
+    Hashtag(&'a str),
+
+    /// A `SUBJECT` tag
+    Subject(&'a str),
+
+    /// An `EXPIRATION` tag
+    Expiration(Timestamp),
+
+    /// A `CONTENT_WARNING` tag, with the reason (which may be empty)
+    ContentWarning(&'a str),
+
+    /// A `LANGUAGE` tag
+    Language(&'a str),
+
+    /// A `GEOHASH` tag
+    Geohash(&'a str),
+
+    /// A `SUMMARY` tag
+    Summary(&'a str),
+
+    /// A `SLUG` tag
+    Slug(&'a str),
+
+    /// A `COVER_IMAGE_URL` tag
+    CoverImageUrl(&'a str),
+
+    /// A `COVER_IMAGE_BLOB` tag, with the blob hash
+    CoverImageBlob([u8; 32]),
+
+    /// A `PUBLISHED_AT` tag
+    PublishedAt(Timestamp),
+
+    /// A tag of a type this crate does not know, with its value
+    Unknown(TagType, &'a [u8]),
+}
+
+impl ParsedTag<'_> {
+    /// The `TagType` of the tag
+    #[must_use]
+    pub fn get_type(&self) -> TagType {
+        match self {
+            ParsedTag::NotifyPublicKey(_) => TagType::NOTIFY_PUBLIC_KEY,
+            ParsedTag::Reply { .. } => TagType::REPLY,
+            ParsedTag::Root { .. } => TagType::ROOT,
+            ParsedTag::NostrSister(_) => TagType::NOSTR_SISTER,
+            ParsedTag::Subkey(_) => TagType::SUBKEY,
+            ParsedTag::UserMention { .. } => TagType::CONTENT_SEGMENT_USER_MENTION,
+            ParsedTag::ServerMention { .. } => TagType::CONTENT_SEGMENT_SERVER_MENTION,
+            ParsedTag::Quote { .. } => TagType::CONTENT_SEGMENT_QUOTE,
+            ParsedTag::Url { .. } => TagType::CONTENT_SEGMENT_URL,
+            ParsedTag::Image { .. } => TagType::CONTENT_SEGMENT_IMAGE,
+            ParsedTag::Video { .. } => TagType::CONTENT_SEGMENT_VIDEO,
+            ParsedTag::Hashtag(_) => TagType::HASHTAG,
+            ParsedTag::Subject(_) => TagType::SUBJECT,
+            ParsedTag::Expiration(_) => TagType::EXPIRATION,
+            ParsedTag::ContentWarning(_) => TagType::CONTENT_WARNING,
+            ParsedTag::Language(_) => TagType::LANGUAGE,
+            ParsedTag::Geohash(_) => TagType::GEOHASH,
+            ParsedTag::Summary(_) => TagType::SUMMARY,
+            ParsedTag::Slug(_) => TagType::SLUG,
+            ParsedTag::CoverImageUrl(_) => TagType::COVER_IMAGE_URL,
+            ParsedTag::CoverImageBlob(_) => TagType::COVER_IMAGE_BLOB,
+            ParsedTag::PublishedAt(_) => TagType::PUBLISHED_AT,
+            ParsedTag::Unknown(ty, _) => *ty,
+        }
+    }
+
+    /// Convert back into an `OwnedTag`
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if a URL or unknown value is too long for a tag
+    pub fn to_owned_tag(&self) -> Result<OwnedTag, Error> {
+        Ok(match *self {
+            ParsedTag::NotifyPublicKey(public_key) => OwnedTag::new_notify_public_key(&public_key),
+            ParsedTag::Reply { reference, kind } => OwnedTag::new_reply(&reference, kind),
+            ParsedTag::Root { reference, kind } => OwnedTag::new_root(&reference, kind),
+            ParsedTag::NostrSister(id) => OwnedTag::new_nostr_sister(&id),
+            ParsedTag::Subkey(public_key) => OwnedTag::new_subkey(&public_key),
+            ParsedTag::UserMention { public_key, offset } => {
+                OwnedTag::new_content_segment_user_mention(&public_key, offset)
+            }
+            ParsedTag::ServerMention { public_key, offset } => {
+                OwnedTag::new_content_segment_server_mention(&public_key, offset)
+            }
+            ParsedTag::Quote {
+                reference,
+                kind,
+                offset,
+            } => OwnedTag::new_content_segment_quote(&reference, kind, offset),
+            ParsedTag::Url { url, offset }
+            | ParsedTag::Image { url, offset }
+            | ParsedTag::Video { url, offset } => {
+                let mut value = offset.to_le_bytes().to_vec();
+                value.extend_from_slice(url.as_bytes());
+                OwnedTag::new(self.get_type(), &value)?
+            }
+            ParsedTag::Hashtag(hashtag) => OwnedTag::new_hashtag(hashtag)?,
+            ParsedTag::Subject(subject) => OwnedTag::new_subject(subject)?,
+            ParsedTag::Expiration(expiration) => OwnedTag::new_expiration(expiration),
+            ParsedTag::ContentWarning(reason) => OwnedTag::new_content_warning(reason)?,
+            ParsedTag::Language(language) => OwnedTag::new_language(language)?,
+            ParsedTag::Geohash(geohash) => OwnedTag::new_geohash(geohash)?,
+            ParsedTag::Summary(summary) => OwnedTag::new_summary(summary)?,
+            ParsedTag::Slug(slug) => OwnedTag::new_slug(slug)?,
+            ParsedTag::CoverImageUrl(url) => OwnedTag::new_cover_image_url(url)?,
+            ParsedTag::CoverImageBlob(hash) => OwnedTag::new_cover_image_blob(&hash),
+            ParsedTag::PublishedAt(published_at) => OwnedTag::new_published_at(published_at),
+            ParsedTag::Unknown(ty, value) => OwnedTag::new(ty, &value)?,
+        })
+    }
+}
+
+impl std::fmt::Display for ParsedTag<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: ", self.get_type())?;
+        match self {
+            ParsedTag::NotifyPublicKey(public_key) | ParsedTag::Subkey(public_key) => {
+                write!(f, "{public_key}")
+            }
+            ParsedTag::Reply { reference, kind } | ParsedTag::Root { reference, kind } => {
+                write!(f, "{reference} ({kind})")
+            }
+            ParsedTag::NostrSister(id) => {
+                for byte in id {
+                    write!(f, "{byte:02x}")?;
+                }
+                Ok(())
+            }
+            ParsedTag::UserMention { public_key, offset }
+            | ParsedTag::ServerMention { public_key, offset } => {
+                write!(f, "{public_key} at {offset}")
+            }
+            ParsedTag::Quote {
+                reference,
+                kind,
+                offset,
+            } => write!(f, "{reference} ({kind}) at {offset}"),
+            ParsedTag::Url { url, offset }
+            | ParsedTag::Image { url, offset }
+            | ParsedTag::Video { url, offset } => write!(f, "{url} at {offset}"),
+            ParsedTag::Hashtag(hashtag) => write!(f, "#{hashtag}"),
+            ParsedTag::Expiration(timestamp) | ParsedTag::PublishedAt(timestamp) => {
+                write!(f, "{timestamp}")
+            }
+            ParsedTag::Subject(text)
+            | ParsedTag::ContentWarning(text)
+            | ParsedTag::Language(text)
+            | ParsedTag::Geohash(text)
+            | ParsedTag::Summary(text)
+            | ParsedTag::Slug(text)
+            | ParsedTag::CoverImageUrl(text) => write!(f, "{text}"),
+            ParsedTag::CoverImageBlob(hash) => {
+                for byte in hash {
+                    write!(f, "{byte:02x}")?;
+                }
+                Ok(())
+            }
+            ParsedTag::Unknown(_, value) => write!(f, "{}", z32::encode(value)),
+        }
+    }
+}
+
+impl TryFrom<ParsedTag<'_>> for OwnedTag {
+    type Error = Error;
+
+    fn try_from(parsed: ParsedTag<'_>) -> Result<OwnedTag, Error> {
+        parsed.to_owned_tag()
+    }
+}
+
 #[cfg(test)]
 macro_rules! test_tag_type {
     ($new:expr, $typ:expr) => {{
@@ -740,4 +1713,151 @@ mod test {
         assert_eq!(v.get_url().unwrap().unwrap(), url);
         assert_eq!(v.get_offset().unwrap(), offset);
     }
+
+    #[test]
+    fn test_parsed_tag() {
+        let public_key = SecretKey::generate().public();
+        let reference = Reference::from_printable(
+            "moref01ge91q91o36bcfrk7qfhpnydyyobh88zknproi8j5791e5mekfez1ye6zrifbhh6m1dtizcsp4y5w",
+        )
+        .unwrap();
+        let url = "https://example.com/meme.jpg";
+
+        let tags = vec![
+            OwnedTag::new_notify_public_key(&public_key),
+            OwnedTag::new_reply(&reference, Kind::MICROBLOG_ROOT),
+            OwnedTag::new_root(&reference, Kind::MICROBLOG_ROOT),
+            OwnedTag::new_nostr_sister(&[7; 32]),
+            OwnedTag::new_subkey(&public_key),
+            OwnedTag::new_content_segment_user_mention(&public_key, 1),
+            OwnedTag::new_content_segment_server_mention(&public_key, 2),
+            OwnedTag::new_content_segment_quote(&reference, Kind::BLOG_POST, 3),
+            OwnedTag::new_content_segment_url(url, 4),
+            OwnedTag::new_content_segment_image(url, 5),
+            OwnedTag::new_content_segment_video(url, 6),
+            OwnedTag::new(TagType(0x7777), b"anything").unwrap(),
+            OwnedTag::new_hashtag("mosaic").unwrap(),
+            OwnedTag::new_subject("On tags").unwrap(),
+            OwnedTag::new_expiration(Timestamp::from_unixtime(1_700_000_000, 0).unwrap()),
+            OwnedTag::new_content_warning("").unwrap(),
+            OwnedTag::new_language("pt-BR").unwrap(),
+            OwnedTag::new_geohash("u4pruydqqvj").unwrap(),
+        ];
+        for tag in &tags {
+            let parsed = tag.parse().unwrap();
+            assert_eq!(parsed.get_type(), tag.get_type());
+            assert_eq!(&parsed.to_owned_tag().unwrap(), tag);
+        }
+
+        assert_eq!(
+            tags[7].parse().unwrap(),
+            ParsedTag::Quote {
+                reference,
+                kind: Kind::BLOG_POST,
+                offset: 3
+            }
+        );
+        assert_eq!(
+            tags[9].parse().unwrap(),
+            ParsedTag::Image { url, offset: 5 }
+        );
+        assert_eq!(
+            tags[11].parse().unwrap(),
+            ParsedTag::Unknown(TagType(0x7777), b"anything")
+        );
+
+        assert_eq!(tags[17].parse().unwrap(), ParsedTag::Geohash("u4pruydqqvj"));
+
+        // Wrong length for the type
+        let tag = OwnedTag::new(TagType::NOTIFY_PUBLIC_KEY, &[0; 4]).unwrap();
+        assert!(tag.parse().is_err());
+        let tag = OwnedTag::new(TagType::CONTENT_SEGMENT_URL, &[0; 2]).unwrap();
+        assert!(tag.parse().is_err());
+
+        // Reserved bytes must be zero
+        for tag in tags[..5].iter().chain(&tags[12..]) {
+            let mut data = tag.data_bytes().to_vec();
+            data[0] = 1;
+            let tag = OwnedTag::new(tag.get_type(), &data).unwrap();
+            assert!(matches!(
+                tag.parse().unwrap_err().inner,
+                InnerError::ReservedSpaceUsed
+            ));
+        }
+    }
+
+    #[test]
+    fn test_metadata_tags() {
+        let expiration = Timestamp::from_unixtime(1_700_000_000, 0).unwrap();
+
+        let v = test_tag_type!(OwnedTag::new_hashtag("#Mosaic").unwrap(), TagType::HASHTAG);
+        assert_eq!(v.get_hashtag().unwrap().unwrap(), "mosaic");
+        assert_eq!(v, OwnedTag::new_hashtag("mosaic").unwrap());
+        assert!(v.get_subject().unwrap().is_none());
+        assert!(OwnedTag::new_hashtag("#").is_err());
+
+        let v = test_tag_type!(OwnedTag::new_subject("Hello").unwrap(), TagType::SUBJECT);
+        assert_eq!(v.get_subject().unwrap().unwrap(), "Hello");
+
+        let v = test_tag_type!(OwnedTag::new_expiration(expiration), TagType::EXPIRATION);
+        assert_eq!(v.get_expiration().unwrap().unwrap(), expiration);
+
+        let v = test_tag_type!(
+            OwnedTag::new_content_warning("spoilers").unwrap(),
+            TagType::CONTENT_WARNING
+        );
+        assert_eq!(v.get_content_warning().unwrap().unwrap(), "spoilers");
+
+        let v = test_tag_type!(OwnedTag::new_language("en").unwrap(), TagType::LANGUAGE);
+        assert_eq!(v.get_language().unwrap().unwrap(), "en");
+        assert!(OwnedTag::new_language("en_US").is_err());
+        assert!(OwnedTag::new_language("en-").is_err());
+
+        let v = test_tag_type!(OwnedTag::new_geohash("ezs42").unwrap(), TagType::GEOHASH);
+        assert_eq!(v.get_geohash().unwrap().unwrap(), "ezs42");
+        assert!(OwnedTag::new_geohash("ezs4a").is_err());
+        assert!(OwnedTag::new_geohash("0123456789bcd").is_err());
+
+        let v = test_tag_type!(OwnedTag::new_summary("In short").unwrap(), TagType::SUMMARY);
+        assert_eq!(v.get_summary().unwrap().unwrap(), "In short");
+
+        let v = test_tag_type!(OwnedTag::new_slug("hello").unwrap(), TagType::SLUG);
+        assert_eq!(v.get_slug().unwrap().unwrap(), "hello");
+
+        let v = test_tag_type!(
+            OwnedTag::new_cover_image_url("https://example.com/a.png").unwrap(),
+            TagType::COVER_IMAGE_URL
+        );
+        assert_eq!(
+            v.get_cover_image_url().unwrap().unwrap(),
+            "https://example.com/a.png"
+        );
+
+        let v = test_tag_type!(
+            OwnedTag::new_cover_image_blob(&[7; 32]),
+            TagType::COVER_IMAGE_BLOB
+        );
+        assert_eq!(v.get_cover_image_blob().unwrap().unwrap(), [7; 32]);
+        assert_eq!(v.parse().unwrap(), ParsedTag::CoverImageBlob([7; 32]));
+
+        let v = test_tag_type!(
+            OwnedTag::new_published_at(expiration),
+            TagType::PUBLISHED_AT
+        );
+        assert_eq!(v.get_published_at().unwrap().unwrap(), expiration);
+        assert!(v.get_expiration().unwrap().is_none());
+        assert_eq!(v.parse().unwrap().to_owned_tag().unwrap(), v);
+
+        // Hashtags match in filters however they were written
+        let record = crate::RecordBuilder::new(Kind::MICROBLOG_ROOT, SecretKey::generate())
+            .timestamp(expiration)
+            .tag(&OwnedTag::new_hashtag("#Rust").unwrap())
+            .unwrap()
+            .sign()
+            .unwrap();
+        let fe =
+            crate::OwnedFilterElement::new_included_tags(&[OwnedTag::new_hashtag("rust").unwrap()])
+                .unwrap();
+        assert!(fe.matches(&record).unwrap());
+    }
 }
@@ -0,0 +1,124 @@
+use crate::{Error, InnerError, TagType};
+use std::collections::BTreeMap;
+use std::sync::{PoisonError, RwLock};
+
+/// How an application-defined `TagType` is named, validated and displayed.
+///
+/// Register it with `TagType::register()`. Tags of the type are then shown
+/// by name wherever tags are displayed (including JSON export), and checked
+/// by `Tag::validate()`.
+#[derive(Debug, Clone, Copy)]
+pub struct TagTypeRegistration {
+    /// The name of the tag type
+    pub name: &'static str,
+
+    /// Checks the value of a tag (the bytes after the length and type)
+    pub validate: fn(&[u8]) -> Result<(), Error>,
+
+    /// Writes the value of a tag (the bytes after the length and type) for
+    /// display. It is only given values that `validate` accepted.
+    pub format: fn(&[u8], &mut std::fmt::Formatter<'_>) -> std::fmt::Result,
+}
+
+static REGISTRY: RwLock<BTreeMap<TagType, TagTypeRegistration>> = RwLock::new(BTreeMap::new());
+
+impl TagType {
+    /// Register an application-defined tag type, for the whole process until
+    /// it is unregistered.
+    ///
+    /// ```
+    /// # use mosaic_core::*;
+    /// TagType(0x8001)
+    ///     .register(TagTypeRegistration {
+    ///         name: "Mood",
+    ///         validate: |value| std::str::from_utf8(value).map(|_| ()).map_err(Into::into),
+    ///         format: |value, f| write!(f, "{}", String::from_utf8_lossy(value)),
+    ///     })
+    ///     .unwrap();
+    ///
+    /// let tag = OwnedTag::new(TagType(0x8001), b"happy").unwrap();
+    /// assert_eq!(tag.to_string(), "Mood: happy");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` if the type is one defined by this crate, or is
+    /// already registered.
+    pub fn register(self, registration: TagTypeRegistration) -> Result<(), Error> {
+        if self.is_built_in() {
+            return Err(InnerError::TagTypeInUse(self).into());
+        }
+        let mut registry = REGISTRY.write().unwrap_or_else(PoisonError::into_inner);
+        if registry.contains_key(&self) {
+            return Err(InnerError::TagTypeInUse(self).into());
+        }
+        let _ = registry.insert(self, registration);
+        Ok(())
+    }
+
+    /// Remove the registration of an application-defined tag type, returning
+    /// it if there was one
+    #[must_use]
+    pub fn unregister(self) -> Option<TagTypeRegistration> {
+        REGISTRY
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&self)
+    }
+
+    /// The registration of an application-defined tag type, if it has been
+    /// registered
+    #[must_use]
+    pub fn registration(&self) -> Option<TagTypeRegistration> {
+        REGISTRY
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(self)
+            .copied()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn test_tag_type_registry() {
+        let registration = TagTypeRegistration {
+            name: "Rating",
+            validate: |value| {
+                if value.len() == 1 && value[0] <= 5 {
+                    Ok(())
+                } else {
+                    Err(InnerError::InvalidTag.into())
+                }
+            },
+            format: |value, f| write!(f, "{} stars", value[0]),
+        };
+
+        // Built-in types can't be registered, nor can types be registered twice
+        assert!(TagType::REPLY.register(registration).is_err());
+        let rating = TagType(0x9001);
+        assert!(rating.registration().is_none());
+        rating.register(registration).unwrap();
+        assert!(rating.register(registration).is_err());
+
+        assert_eq!(rating.name(), Some("Rating"));
+        assert_eq!(rating.to_string(), "Rating");
+        assert_eq!(TagType::HASHTAG.to_string(), "Hashtag");
+        assert_eq!(TagType(0x9002).to_string(), "TagType(36866)");
+
+        let tag = OwnedTag::new(rating, &[4]).unwrap();
+        tag.validate().unwrap();
+        assert_eq!(tag.to_string(), "Rating: 4 stars");
+
+        let tag = OwnedTag::new(rating, &[6]).unwrap();
+        assert!(tag.validate().is_err());
+        assert!(tag.to_string().starts_with("Rating: (invalid)"));
+
+        assert_eq!(rating.unregister().map(|r| r.name), Some("Rating"));
+        assert!(rating.unregister().is_none());
+        assert!(rating.registration().is_none());
+        assert_eq!(rating.to_string(), "TagType(36865)");
+    }
+}
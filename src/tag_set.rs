@@ -1,8 +1,13 @@
-use crate::{Error, InnerError, Tag};
+use crate::{Error, InnerError, Tag, TagType};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::ops::{Deref, DerefMut};
 
+/// The maximum length of a `TagSet` in bytes, as limited by the record
+/// header
+pub const MAX_TAG_SET_LEN: usize = 65_535;
+
 /// A sequence of `Tag`s, borrowed
 ///
 /// See also `OwnedTagSet` for the owned variant.
@@ -71,6 +76,10 @@ impl TagSet {
     }
 
     /// Iterator over tags
+    ///
+    /// The iterator panics on invalid data, which can only be present if the
+    /// `TagSet` was made with `from_bytes_unchecked()`. Use `try_iter()` for
+    /// such data.
     #[must_use]
     pub fn iter(&self) -> TagSetIter<'_> {
         TagSetIter {
@@ -78,6 +87,33 @@ impl TagSet {
             p: 0,
         }
     }
+
+    /// Iterator over tags that yields an `Err` (and then stops) at invalid
+    /// data instead of panicking
+    #[must_use]
+    pub fn try_iter(&self) -> TagSetTryIter<'_> {
+        TagSetTryIter {
+            bytes: &self.0,
+            p: 0,
+        }
+    }
+
+    /// Iterator over the tags of the `TagType`
+    pub fn tags_of_type(&self, ty: TagType) -> impl Iterator<Item = &Tag> {
+        self.iter().filter(move |tag| tag.get_type() == ty)
+    }
+
+    /// The first tag of the `TagType`
+    #[must_use]
+    pub fn first_of_type(&self, ty: TagType) -> Option<&Tag> {
+        self.tags_of_type(ty).next()
+    }
+
+    /// Does the set contain the tag?
+    #[must_use]
+    pub fn contains(&self, tag: &Tag) -> bool {
+        self.iter().any(|t| t == tag)
+    }
 }
 
 impl<'a> IntoIterator for &'a TagSet {
@@ -109,6 +145,36 @@ impl<'a> Iterator for TagSetIter<'a> {
     }
 }
 
+/// An iterator of `Tag`s in `TagSet` that checks each one
+///
+/// See `TagSet::try_iter()`.
+#[derive(Debug)]
+pub struct TagSetTryIter<'a> {
+    bytes: &'a [u8],
+    p: usize,
+}
+
+impl<'a> Iterator for TagSetTryIter<'a> {
+    type Item = Result<&'a Tag, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = self.bytes.get(self.p..)?;
+        if rest.is_empty() {
+            return None;
+        }
+        match Tag::from_bytes(rest) {
+            Ok(tag) => {
+                self.p += tag.as_bytes().len();
+                Some(Ok(tag))
+            }
+            Err(e) => {
+                self.p = self.bytes.len();
+                Some(Err(e))
+            }
+        }
+    }
+}
+
 /// An owned set of `Tag`s
 ///
 /// See `TagSet` for the borrowed variant.
@@ -139,9 +205,65 @@ impl OwnedTagSet {
     }
 
     /// Add a tag
+    ///
+    /// This does not check the length of the set; records cannot be made with
+    /// sets longer than `MAX_TAG_SET_LEN`. See `try_add_tag()`.
     pub fn add_tag(&mut self, tag: &Tag) {
         self.0.extend(tag.as_bytes());
     }
+
+    /// Add a tag, if the set would not exceed `MAX_TAG_SET_LEN`
+    ///
+    /// # Errors
+    ///
+    /// Returns an `Err` (and leaves the set unchanged) if the set would become
+    /// too long.
+    pub fn try_add_tag(&mut self, tag: &Tag) -> Result<(), Error> {
+        if tag.as_bytes().len() > self.remaining_len() {
+            return Err(InnerError::TagSetTooLong.into());
+        }
+        self.add_tag(tag);
+        Ok(())
+    }
+
+    /// The number of bytes of tags that can still be added
+    #[must_use]
+    pub fn remaining_len(&self) -> usize {
+        MAX_TAG_SET_LEN.saturating_sub(self.0.len())
+    }
+
+    /// Remove every tag of the `TagType`, returning how many were removed
+    pub fn remove_type(&mut self, ty: TagType) -> usize {
+        self.retain(|tag| tag.get_type() != ty)
+    }
+
+    /// Remove tags that are the same as an earlier tag
+    pub fn dedup(&mut self) {
+        let mut seen: HashSet<&[u8]> = HashSet::new();
+        let mut bytes: Vec<u8> = Vec::with_capacity(self.0.len());
+        for tag in self.iter() {
+            if seen.insert(tag.as_bytes()) {
+                bytes.extend(tag.as_bytes());
+            }
+        }
+        self.0 = bytes;
+    }
+
+    // Keep only the tags for which `f` is true, returning how many were
+    // removed
+    fn retain<F: FnMut(&Tag) -> bool>(&mut self, mut f: F) -> usize {
+        let mut removed = 0;
+        let mut bytes: Vec<u8> = Vec::with_capacity(self.0.len());
+        for tag in self.iter() {
+            if f(tag) {
+                bytes.extend(tag.as_bytes());
+            } else {
+                removed += 1;
+            }
+        }
+        self.0 = bytes;
+        removed
+    }
 }
 
 impl Default for OwnedTagSet {
@@ -260,4 +382,74 @@ mod test {
 
         let _owned_tag_set = OwnedTagSet::from_tags(tags.iter().map(|t| &**t));
     }
+
+    #[test]
+    fn test_tag_set_helpers() {
+        let public_key = SecretKey::generate().public();
+        let notify = OwnedTag::new_notify_public_key(&public_key);
+        let hashtag = OwnedTag::new_hashtag("mosaic").unwrap();
+        let hashtag2 = OwnedTag::new_hashtag("rust").unwrap();
+
+        let mut tag_set = OwnedTagSet::new();
+        for tag in [&notify, &hashtag, &notify, &hashtag2] {
+            tag_set.try_add_tag(tag).unwrap();
+        }
+
+        assert_eq!(tag_set.tags_of_type(TagType::HASHTAG).count(), 2);
+        assert_eq!(tag_set.first_of_type(TagType::HASHTAG), Some(&*hashtag));
+        assert_eq!(tag_set.first_of_type(TagType::SUBJECT), None);
+        assert!(tag_set.contains(&hashtag2));
+
+        tag_set.dedup();
+        assert_eq!(tag_set.iter().count(), 3);
+        assert_eq!(tag_set.remove_type(TagType::HASHTAG), 2);
+        assert_eq!(tag_set.iter().collect::<Vec<_>>(), vec![&*notify]);
+
+        // Tags that would make the set too long are refused
+        let big = OwnedTag::new(TagType(100), &vec![0; 60_000]).unwrap();
+        tag_set.try_add_tag(&big).unwrap();
+        let len = tag_set.as_bytes().len();
+        assert!(matches!(
+            tag_set.try_add_tag(&big).unwrap_err().inner,
+            InnerError::TagSetTooLong
+        ));
+        assert_eq!(tag_set.as_bytes().len(), len);
+        assert_eq!(tag_set.remaining_len(), MAX_TAG_SET_LEN - len);
+    }
+
+    #[test]
+    fn test_tag_set_try_iter() {
+        let example: Vec<u8> = vec![
+            8, 0, // length
+            1, 0, // type 1,
+            10, 9, 8, 7, // data
+            10, 0, // length, beyond the end
+            2, 0, // type 2
+            1, 2, // data
+        ];
+
+        let tag_set = unsafe { TagSet::from_bytes_unchecked(&example) };
+        let mut iter = tag_set.try_iter();
+        assert_eq!(iter.next().unwrap().unwrap().get_type(), TagType(1));
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().is_none());
+
+        // A length too short for the length and type
+        let example: Vec<u8> = vec![
+            8, 0, // length
+            1, 0, // type 1,
+            10, 9, 8, 7, // data
+            2, 0, // length, shorter than the header
+            2, 0, // type 2
+        ];
+        assert!(TagSet::from_bytes(&example).is_err());
+        let tag_set = unsafe { TagSet::from_bytes_unchecked(&example) };
+        let mut iter = tag_set.try_iter();
+        assert_eq!(iter.next().unwrap().unwrap().get_type(), TagType(1));
+        assert!(matches!(
+            iter.next().unwrap().unwrap_err().inner,
+            InnerError::InvalidTag
+        ));
+        assert!(iter.next().is_none());
+    }
 }
@@ -0,0 +1,333 @@
+use crate::{OwnedRecord, ParsedTag, Record, Reference};
+use std::collections::HashMap;
+
+/// A conversation reconstructed from the `REPLY` and `ROOT` tags of a set of
+/// records
+///
+/// Records that reply to nothing are roots. Records whose parent is not in
+/// the set are kept as orphans, and the references that would complete the
+/// thread are listed by `missing()`. Siblings are sorted by timestamp.
+///
+/// References to an `Address` resolve to the latest version present.
+#[derive(Debug, Clone)]
+pub struct Thread {
+    records: Vec<OwnedRecord>,
+    parents: Vec<Option<usize>>,
+    children: Vec<Vec<usize>>,
+    roots: Vec<usize>,
+    orphans: Vec<usize>,
+    by_reference: HashMap<Reference, usize>,
+    missing: Vec<Reference>,
+}
+
+impl Thread {
+    /// Build a `Thread` from records. Duplicate records are dropped.
+    #[must_use]
+    pub fn new<I: IntoIterator<Item = OwnedRecord>>(records: I) -> Thread {
+        let mut records: Vec<OwnedRecord> = records.into_iter().collect();
+        records.sort_by_key(|r| (r.timestamp(), r.id()));
+        records.dedup_by_key(|r| r.id());
+
+        // Ids resolve to their record, addresses to their latest version (the
+        // records are in timestamp order)
+        let mut by_reference: HashMap<Reference, usize> = HashMap::new();
+        for (i, record) in records.iter().enumerate() {
+            let _ = by_reference.insert(record.id().to_reference(), i);
+            let _ = by_reference.insert(record.address().to_reference(), i);
+        }
+
+        let mut missing: Vec<Reference> = Vec::new();
+        let mut want = |reference: Reference| {
+            if !by_reference.contains_key(&reference) && !missing.contains(&reference) {
+                missing.push(reference);
+            }
+        };
+
+        let mut parents: Vec<Option<usize>> = vec![None; records.len()];
+        let mut orphans: Vec<usize> = Vec::new();
+        for (i, record) in records.iter().enumerate() {
+            let (reply, root) = reply_and_root(record);
+            if let Some(root) = root {
+                want(root);
+            }
+            if let Some(reply) = reply {
+                want(reply);
+                match by_reference.get(&reply) {
+                    Some(&parent) if parent != i => parents[i] = Some(parent),
+                    _ => orphans.push(i),
+                }
+            }
+        }
+
+        // Break reply cycles, leaving the oldest record of each an orphan.
+        // Each record is walked once: reaching a record that is still on the
+        // current path closes a cycle.
+        let mut visits: Vec<Visit> = vec![Visit::New; records.len()];
+        let mut path: Vec<usize> = Vec::new();
+        for start in 0..records.len() {
+            let mut at = Some(start);
+            while let Some(i) = at {
+                match visits[i] {
+                    Visit::New => {
+                        visits[i] = Visit::InProgress;
+                        path.push(i);
+                        at = parents[i];
+                    }
+                    Visit::InProgress => {
+                        let mut oldest = i;
+                        let mut p = parents[i];
+                        while let Some(q) = p.filter(|&q| q != i) {
+                            oldest = oldest.min(q);
+                            p = parents[q];
+                        }
+                        parents[oldest] = None;
+                        orphans.push(oldest);
+                        break;
+                    }
+                    Visit::Done => break,
+                }
+            }
+            for i in path.drain(..) {
+                visits[i] = Visit::Done;
+            }
+        }
+        orphans.sort_unstable();
+
+        let mut children: Vec<Vec<usize>> = vec![Vec::new(); records.len()];
+        let mut roots: Vec<usize> = Vec::new();
+        for (i, parent) in parents.iter().enumerate() {
+            match parent {
+                Some(p) => children[*p].push(i),
+                None if orphans.binary_search(&i).is_err() => roots.push(i),
+                None => {}
+            }
+        }
+
+        Thread {
+            records,
+            parents,
+            children,
+            roots,
+            orphans,
+            by_reference,
+            missing,
+        }
+    }
+
+    /// The number of records in the thread
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Is the thread empty?
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// The records that do not reply to anything, oldest first
+    #[must_use]
+    pub fn roots(&self) -> Vec<ThreadNode<'_>> {
+        self.nodes(&self.roots)
+    }
+
+    /// The records that reply to a record not in the thread, oldest first
+    #[must_use]
+    pub fn orphans(&self) -> Vec<ThreadNode<'_>> {
+        self.nodes(&self.orphans)
+    }
+
+    /// The references from `REPLY` and `ROOT` tags that are not in the
+    /// thread, and could be fetched to complete it
+    #[must_use]
+    pub fn missing(&self) -> &[Reference] {
+        &self.missing
+    }
+
+    /// Get the record with the reference
+    #[must_use]
+    pub fn get(&self, reference: &Reference) -> Option<ThreadNode<'_>> {
+        self.by_reference.get(reference).map(|&index| ThreadNode {
+            thread: self,
+            index,
+        })
+    }
+
+    fn nodes(&self, indices: &[usize]) -> Vec<ThreadNode<'_>> {
+        indices
+            .iter()
+            .map(|&index| ThreadNode {
+                thread: self,
+                index,
+            })
+            .collect()
+    }
+}
+
+/// A record within a `Thread`
+#[derive(Debug, Clone, Copy)]
+pub struct ThreadNode<'a> {
+    thread: &'a Thread,
+    index: usize,
+}
+
+impl<'a> ThreadNode<'a> {
+    /// The record
+    #[must_use]
+    pub fn record(&self) -> &'a Record {
+        &self.thread.records[self.index]
+    }
+
+    /// The record this replies to, if it is in the thread
+    #[must_use]
+    pub fn parent(&self) -> Option<ThreadNode<'a>> {
+        self.thread.parents[self.index].map(|index| ThreadNode {
+            thread: self.thread,
+            index,
+        })
+    }
+
+    /// The replies to this record, oldest first
+    #[must_use]
+    pub fn children(&self) -> Vec<ThreadNode<'a>> {
+        self.thread.nodes(&self.thread.children[self.index])
+    }
+
+    /// Does this reply to a record that is not in the thread?
+    #[must_use]
+    pub fn is_orphan(&self) -> bool {
+        self.thread.orphans.binary_search(&self.index).is_ok()
+    }
+}
+
+// The state of a record while looking for reply cycles
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Visit {
+    New,
+    InProgress,
+    Done,
+}
+
+// The references of the first REPLY and ROOT tags
+fn reply_and_root(record: &Record) -> (Option<Reference>, Option<Reference>) {
+    let mut reply = None;
+    let mut root = None;
+    for tag in record.tag_set() {
+        match tag.parse() {
+            Ok(ParsedTag::Reply { reference, .. }) if reply.is_none() => reply = Some(reference),
+            Ok(ParsedTag::Root { reference, .. }) if root.is_none() => root = Some(reference),
+            _ => {}
+        }
+    }
+    (reply, root)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn test_thread() {
+        let secret_key = SecretKey::generate();
+        let at = |secs: u64| Timestamp::from_unixtime(1_700_000_000 + secs, 0).unwrap();
+        let post = |secs: u64, tags: &[OwnedTag]| {
+            let mut builder =
+                RecordBuilder::new(Kind::MICROBLOG_ROOT, secret_key.clone()).timestamp(at(secs));
+            for tag in tags {
+                builder = builder.tag(tag).unwrap();
+            }
+            builder.sign().unwrap()
+        };
+        let reply_to = |record: &OwnedRecord, by_address: bool| {
+            let reference = if by_address {
+                record.address().to_reference()
+            } else {
+                record.id().to_reference()
+            };
+            OwnedTag::new_reply(&reference, record.kind())
+        };
+
+        let root = post(0, &[]);
+        let root_tag = OwnedTag::new_root(&root.id().to_reference(), root.kind());
+        let a = post(20, &[reply_to(&root, false), root_tag.clone()]);
+        let b = post(10, &[reply_to(&root, true), root_tag.clone()]);
+        let c = post(30, &[reply_to(&b, false), root_tag.clone()]);
+        let missing = post(5, &[]);
+        let orphan = post(40, &[reply_to(&missing, false), root_tag.clone()]);
+
+        let thread = Thread::new(vec![
+            c.clone(),
+            orphan.clone(),
+            a.clone(),
+            root.clone(),
+            b.clone(),
+            a.clone(),
+        ]);
+        assert_eq!(thread.len(), 5);
+
+        let roots = thread.roots();
+        assert_eq!(roots.len(), 1);
+        assert_eq!(roots[0].record(), &*root);
+
+        // Siblings by timestamp, with both id and address references resolved
+        let children = roots[0].children();
+        assert_eq!(children.len(), 2);
+        assert_eq!(children[0].record(), &*b);
+        assert_eq!(children[1].record(), &*a);
+        assert_eq!(children[0].children()[0].record(), &*c);
+        assert_eq!(children[0].children()[0].parent().unwrap().record(), &*b);
+
+        let orphans = thread.orphans();
+        assert_eq!(orphans.len(), 1);
+        assert_eq!(orphans[0].record(), &*orphan);
+        assert!(orphans[0].is_orphan());
+        assert_eq!(thread.missing(), &[missing.id().to_reference()]);
+
+        // The root is missing too when only replies are present
+        let thread = Thread::new(vec![a.clone(), c.clone()]);
+        assert!(thread.roots().is_empty());
+        assert_eq!(thread.orphans().len(), 2);
+        assert_eq!(
+            thread.missing(),
+            &[root.id().to_reference(), b.id().to_reference()]
+        );
+        assert!(thread.get(&a.id().to_reference()).is_some());
+    }
+
+    #[test]
+    fn test_thread_cycle() {
+        let secret_key = SecretKey::generate();
+        let kind = Kind::MICROBLOG_ROOT;
+        let address = |key: &[u8]| Address::new_deterministic(secret_key.public(), kind, key);
+        let post = |secs: u64, key: &[u8], reply_to: &Reference| {
+            RecordBuilder::new(kind, secret_key.clone())
+                .deterministic_address(key)
+                .timestamp(Timestamp::from_unixtime(1_700_000_000 + secs, 0).unwrap())
+                .tag(&OwnedTag::new_reply(reply_to, kind))
+                .unwrap()
+                .sign()
+                .unwrap()
+        };
+
+        // Two records that reply to each other, and a reply to one of them
+        let x = post(10, b"x", &address(b"y").to_reference());
+        let y = post(20, b"y", &address(b"x").to_reference());
+        let z = post(30, b"z", &y.id().to_reference());
+
+        let thread = Thread::new(vec![z.clone(), y.clone(), x.clone()]);
+        assert!(thread.roots().is_empty());
+        assert!(thread.missing().is_empty());
+
+        // The oldest record of the cycle is left an orphan
+        let orphans = thread.orphans();
+        assert_eq!(orphans.len(), 1);
+        assert_eq!(orphans[0].record(), &*x);
+        assert!(orphans[0].parent().is_none());
+        let children = orphans[0].children();
+        assert_eq!(children.len(), 1);
+        assert_eq!(children[0].record(), &*y);
+        assert_eq!(children[0].children()[0].record(), &*z);
+    }
+}
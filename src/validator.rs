@@ -0,0 +1,380 @@
+use crate::{Kind, Record, RecordFlags, ResultCode, TagType, Timestamp};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+/// A way in which a `Record` breaks the policy of a `RecordValidator`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    /// The timestamp is further in the future than allowed
+    TimestampInFuture(Timestamp),
+
+    /// The payload is longer than allowed for the kind
+    PayloadTooLong {
+        /// The length of the payload
+        len: usize,
+
+        /// The maximum length allowed
+        max: usize,
+    },
+
+    /// The kind has printable content, but the payload is not valid UTF-8
+    ContentNotUtf8,
+
+    /// The payload is not valid for the kind (see `KindRegistration`)
+    InvalidPayload(Kind),
+
+    /// A tag is of a type that is not accepted
+    UnknownTagType(TagType),
+
+    /// A tag is not valid for its type
+    InvalidTag(TagType),
+
+    /// The kind requires the `FROM_AUTHOR` flag, but it is not set
+    MissingFromAuthorFlag,
+
+    /// Any other violation, as reported by a custom `ValidationRule`
+    Other(ResultCode, String),
+}
+
+impl Violation {
+    /// The `ResultCode` to answer a submission with this violation
+    #[must_use]
+    pub fn result_code(&self) -> ResultCode {
+        match self {
+            Violation::PayloadTooLong { .. } => ResultCode::TooLarge,
+            Violation::TimestampInFuture(_)
+            | Violation::ContentNotUtf8
+            | Violation::InvalidPayload(_)
+            | Violation::UnknownTagType(_)
+            | Violation::InvalidTag(_)
+            | Violation::MissingFromAuthorFlag => ResultCode::Invalid,
+            Violation::Other(code, _) => *code,
+        }
+    }
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Violation::TimestampInFuture(t) => write!(f, "Timestamp is too far in the future: {t}"),
+            Violation::PayloadTooLong { len, max } => {
+                write!(f, "Payload is too long: {len} > {max} bytes")
+            }
+            Violation::ContentNotUtf8 => write!(f, "Printable content is not valid UTF-8"),
+            Violation::InvalidPayload(k) => write!(f, "Payload is not valid for {k}"),
+            Violation::UnknownTagType(t) => write!(f, "Unknown tag type: {t}"),
+            Violation::InvalidTag(t) => write!(f, "Invalid {t} tag"),
+            Violation::MissingFromAuthorFlag => write!(f, "FROM_AUTHOR flag is required"),
+            Violation::Other(_, s) => write!(f, "{s}"),
+        }
+    }
+}
+
+/// A policy rule applied by a `RecordValidator`
+pub trait ValidationRule: Send + Sync {
+    /// Check the record against this rule, at the time `now`
+    ///
+    /// # Errors
+    ///
+    /// Returns the `Violation` if the record breaks the rule
+    fn check(&self, record: &Record, now: Timestamp) -> Result<(), Violation>;
+}
+
+/// Checks `Record`s against policy rules, beyond their cryptographic
+/// validity (see `Record::verify()`).
+///
+/// ```
+/// # use mosaic_core::*;
+/// # use std::time::Duration;
+/// let validator = RecordValidator::new()
+///     .rule(MaxFutureTimestamp(Duration::from_mins(10)))
+///     .rule(MaxPayloadLen::new(65_536).kind(Kind::CHAT_MESSAGE, 4_096))
+///     .rule(PrintableContentIsUtf8)
+///     .rule(PayloadMatchesKind)
+///     .rule(KnownTagTypes::default())
+///     .rule(RequireFromAuthor::new([Kind::KEY_SCHEDULE]));
+/// ```
+#[derive(Default)]
+pub struct RecordValidator {
+    rules: Vec<Box<dyn ValidationRule>>,
+}
+
+impl std::fmt::Debug for RecordValidator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RecordValidator")
+            .field("rules", &self.rules.len())
+            .finish()
+    }
+}
+
+impl RecordValidator {
+    /// Create a `RecordValidator` with no rules
+    #[must_use]
+    pub fn new() -> RecordValidator {
+        RecordValidator::default()
+    }
+
+    /// Add a rule
+    #[must_use]
+    pub fn rule<R: ValidationRule + 'static>(mut self, rule: R) -> RecordValidator {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    /// Check the `Record` against every rule, at the time `now`
+    ///
+    /// # Errors
+    ///
+    /// Returns every `Violation`, in the order the rules were added
+    pub fn validate(&self, record: &Record, now: Timestamp) -> Result<(), Vec<Violation>> {
+        let violations: Vec<Violation> = self
+            .rules
+            .iter()
+            .filter_map(|rule| rule.check(record, now).err())
+            .collect();
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
+
+/// Rejects timestamps more than the `Duration` in the future
+#[derive(Debug, Clone, Copy)]
+pub struct MaxFutureTimestamp(pub Duration);
+
+impl ValidationRule for MaxFutureTimestamp {
+    fn check(&self, record: &Record, now: Timestamp) -> Result<(), Violation> {
+        if record.timestamp() > now + self.0 {
+            Err(Violation::TimestampInFuture(record.timestamp()))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Caps the (stored, possibly compressed) payload length, optionally
+/// per `Kind`
+#[derive(Debug, Clone)]
+pub struct MaxPayloadLen {
+    default: usize,
+    per_kind: HashMap<Kind, usize>,
+}
+
+impl MaxPayloadLen {
+    /// Cap payloads of every kind at `default` bytes
+    #[must_use]
+    pub fn new(default: usize) -> MaxPayloadLen {
+        MaxPayloadLen {
+            default,
+            per_kind: HashMap::new(),
+        }
+    }
+
+    /// Cap payloads of the `Kind` at `max` bytes instead
+    #[must_use]
+    pub fn kind(mut self, kind: Kind, max: usize) -> MaxPayloadLen {
+        let _ = self.per_kind.insert(kind, max);
+        self
+    }
+}
+
+impl ValidationRule for MaxPayloadLen {
+    fn check(&self, record: &Record, _now: Timestamp) -> Result<(), Violation> {
+        let max = *self.per_kind.get(&record.kind()).unwrap_or(&self.default);
+        let len = record.payload_len();
+        if len > max {
+            Err(Violation::PayloadTooLong { len, max })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Requires the payload to be valid UTF-8 when
+/// `Kind::content_is_printable()`. Compressed payloads are decompressed
+/// first; a payload that cannot be decompressed is a violation.
+#[derive(Debug, Clone, Copy)]
+pub struct PrintableContentIsUtf8;
+
+impl ValidationRule for PrintableContentIsUtf8 {
+    fn check(&self, record: &Record, _now: Timestamp) -> Result<(), Violation> {
+        if !record.kind().content_is_printable() {
+            return Ok(());
+        }
+        match record.payload() {
+            Ok(payload) if std::str::from_utf8(&payload).is_ok() => Ok(()),
+            _ => Err(Violation::ContentNotUtf8),
+        }
+    }
+}
+
+/// Requires the payload to pass the validator registered for the record's
+/// kind (see `Kind::registration()`), if it has one. Compressed payloads are
+/// decompressed first; a payload that cannot be decompressed is a violation.
+#[derive(Debug, Clone, Copy)]
+pub struct PayloadMatchesKind;
+
+impl ValidationRule for PayloadMatchesKind {
+    fn check(&self, record: &Record, _now: Timestamp) -> Result<(), Violation> {
+        let Some(validate) = record.kind().registration().and_then(|r| r.validate) else {
+            return Ok(());
+        };
+        match record.payload() {
+            Ok(payload) if validate(&payload).is_ok() => Ok(()),
+            _ => Err(Violation::InvalidPayload(record.kind())),
+        }
+    }
+}
+
+/// Rejects tags of types not in the set (nor registered with
+/// `TagType::register()`), and tags that are not valid for their type (see
+/// `Tag::validate()`)
+#[derive(Debug, Clone)]
+pub struct KnownTagTypes(pub HashSet<TagType>);
+
+impl Default for KnownTagTypes {
+    /// The core tag types defined by this crate
+    fn default() -> KnownTagTypes {
+        KnownTagTypes(
+            [
+                TagType::NOTIFY_PUBLIC_KEY,
+                TagType::REPLY,
+                TagType::ROOT,
+                TagType::NOSTR_SISTER,
+                TagType::SUBKEY,
+                TagType::CONTENT_SEGMENT_USER_MENTION,
+                TagType::CONTENT_SEGMENT_SERVER_MENTION,
+                TagType::CONTENT_SEGMENT_QUOTE,
+                TagType::CONTENT_SEGMENT_URL,
+                TagType::CONTENT_SEGMENT_IMAGE,
+                TagType::CONTENT_SEGMENT_VIDEO,
+                TagType::HASHTAG,
+                TagType::SUBJECT,
+                TagType::EXPIRATION,
+                TagType::CONTENT_WARNING,
+                TagType::LANGUAGE,
+                TagType::GEOHASH,
+                TagType::SUMMARY,
+                TagType::SLUG,
+                TagType::COVER_IMAGE_URL,
+                TagType::COVER_IMAGE_BLOB,
+                TagType::PUBLISHED_AT,
+            ]
+            .into_iter()
+            .collect(),
+        )
+    }
+}
+
+impl ValidationRule for KnownTagTypes {
+    fn check(&self, record: &Record, _now: Timestamp) -> Result<(), Violation> {
+        for tag in record.tag_set() {
+            let ty = tag.get_type();
+            if !self.0.contains(&ty) && ty.registration().is_none() {
+                return Err(Violation::UnknownTagType(ty));
+            }
+            if tag.validate().is_err() {
+                return Err(Violation::InvalidTag(ty));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Requires the `FROM_AUTHOR` flag on records of the given kinds
+#[derive(Debug, Clone)]
+pub struct RequireFromAuthor(pub HashSet<Kind>);
+
+impl RequireFromAuthor {
+    /// Require the flag on the `kinds`
+    #[must_use]
+    pub fn new<I: IntoIterator<Item = Kind>>(kinds: I) -> RequireFromAuthor {
+        RequireFromAuthor(kinds.into_iter().collect())
+    }
+}
+
+impl ValidationRule for RequireFromAuthor {
+    fn check(&self, record: &Record, _now: Timestamp) -> Result<(), Violation> {
+        if self.0.contains(&record.kind()) && !record.flags().contains(RecordFlags::FROM_AUTHOR) {
+            Err(Violation::MissingFromAuthorFlag)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::*;
+
+    #[test]
+    fn test_record_validator() {
+        let secret_key = SecretKey::generate();
+        let now = Timestamp::from_unixtime(1_700_000_000, 0).unwrap();
+        let validator = RecordValidator::new()
+            .rule(MaxFutureTimestamp(Duration::from_mins(10)))
+            .rule(MaxPayloadLen::new(1_000).kind(Kind::CHAT_MESSAGE, 10))
+            .rule(PrintableContentIsUtf8)
+            .rule(PayloadMatchesKind)
+            .rule(KnownTagTypes::default())
+            .rule(RequireFromAuthor::new([Kind::BLOG_POST]));
+
+        let builder = |kind: Kind| {
+            RecordBuilder::new(kind, secret_key.clone())
+                .timestamp(now)
+                .payload(b"Hello World!")
+                .unwrap()
+        };
+
+        // A good record
+        let record = builder(Kind::MICROBLOG_ROOT).sign().unwrap();
+        validator.validate(&record, now).unwrap();
+
+        // Several violations at once
+        let record = builder(Kind::CHAT_MESSAGE)
+            .timestamp(now + Duration::from_secs(601))
+            .tag(&OwnedTag::new(TagType(0x7777), b"x").unwrap())
+            .unwrap()
+            .sign()
+            .unwrap();
+        let violations = validator.validate(&record, now).unwrap_err();
+        assert_eq!(
+            violations,
+            vec![
+                Violation::TimestampInFuture(now + Duration::from_secs(601)),
+                Violation::PayloadTooLong { len: 12, max: 10 },
+                Violation::UnknownTagType(TagType(0x7777)),
+            ]
+        );
+        assert_eq!(violations[0].result_code(), ResultCode::Invalid);
+        assert_eq!(violations[1].result_code(), ResultCode::TooLarge);
+
+        let record = builder(Kind::BLOG_POST)
+            .payload(&[0xff, 0xfe])
+            .unwrap()
+            .sign()
+            .unwrap();
+        assert_eq!(
+            validator.validate(&record, now).unwrap_err(),
+            vec![
+                Violation::ContentNotUtf8,
+                Violation::InvalidPayload(Kind::BLOG_POST),
+                Violation::MissingFromAuthorFlag
+            ]
+        );
+        let record = builder(Kind::BLOG_POST)
+            .flags(RecordFlags::FROM_AUTHOR)
+            .sign()
+            .unwrap();
+        validator.validate(&record, now).unwrap();
+
+        let record = builder(Kind::PROFILE).sign().unwrap();
+        assert_eq!(
+            validator.validate(&record, now).unwrap_err(),
+            vec![Violation::InvalidPayload(Kind::PROFILE)]
+        );
+    }
+}